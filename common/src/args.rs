@@ -0,0 +1,25 @@
+// Options de la ligne de commande, passées après `--`
+
+// Valeur qui suit `flag` : `flag_value(&args, "--radix")` vaut `Some("16")` pour `--radix 16`
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|v| v.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_value() {
+        let args: Vec<String> = "day00 --radix 16 --torus"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        assert_eq!(flag_value(&args, "--radix"), Some("16"));
+        assert_eq!(flag_value(&args, "--torus"), None);
+        assert_eq!(flag_value(&args, "--check"), None);
+    }
+}
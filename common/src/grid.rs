@@ -0,0 +1,356 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+// Voisinage de von Neumann (N, E, S, W)
+pub const DIRS4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+// Voisinage de Moore (N, NE, E, SE, S, SW, W, NW)
+pub const DIRS8: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub fn new(row: usize, col: usize) -> Pos {
+        Pos { row, col }
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+// Ligne (à partir de 0) dont la longueur diffère de celle de la première
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaggedRow {
+    pub row: usize,
+    pub len: usize,
+    pub expected: usize,
+}
+
+impl fmt::Display for RaggedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "row {} has {} cells, expected {}",
+            self.row, self.len, self.expected
+        )
+    }
+}
+
+// Grille rectangulaire stockée à plat, ligne par ligne
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // Toutes les lignes doivent avoir la même longueur
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        Grid::try_from_rows(rows).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Comme `from_rows`, mais une ligne de longueur différente de la première est une erreur
+    pub fn try_from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, RaggedRow> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |r| r.len());
+        if let Some(row) = rows.iter().position(|r| r.len() != width) {
+            return Err(RaggedRow {
+                row,
+                len: rows[row].len(),
+                expected: width,
+            });
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    // Les lignes plus courtes que la plus longue sont complétées avec `fill`
+    pub fn from_rows_padded(mut rows: Vec<Vec<T>>, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize(width, fill.clone());
+        }
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if pos.row < self.height && pos.col < self.width {
+            Some(&self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if pos.row < self.height && pos.col < self.width {
+            Some(&mut self.cells[pos.row * self.width + pos.col])
+        } else {
+            None
+        }
+    }
+
+    // Position décalée de (dr, dc), si elle reste dans la grille
+    pub fn offset(&self, pos: Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+        let row = pos.row as isize + dr;
+        let col = pos.col as isize + dc;
+        self.in_bounds(row, col)
+            .then(|| Pos::new(row as usize, col as usize))
+    }
+
+    // Position décalée de (dr, dc) sur un tore : on ressort du côté opposé
+    pub fn wrapping_offset(&self, pos: Pos, (dr, dc): (isize, isize)) -> Pos {
+        let row = (pos.row as isize + dr).rem_euclid(self.height as isize);
+        let col = (pos.col as isize + dc).rem_euclid(self.width as isize);
+        Pos::new(row as usize, col as usize)
+    }
+
+    pub fn neighbors<'a>(
+        &'a self,
+        pos: Pos,
+        dirs: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        dirs.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbors(pos, &DIRS4)
+    }
+
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbors(pos, &DIRS8)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| Pos::new(row, col)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `max(1)` : `chunks` refuse une taille nulle (grille vide)
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "Column {} out of the grid", col);
+        self.cells[col..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    pub fn transposed(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    pub fn position(&self, pred: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, v)| pred(v)).map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(input: &str) -> Grid<char> {
+        Grid::from_rows(input.lines().map(|line| line.chars().collect()).collect())
+    }
+
+    pub fn try_parse(input: &str) -> Result<Grid<char>, RaggedRow> {
+        Grid::try_from_rows(input.lines().map(|line| line.chars().collect()).collect())
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{} out of the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{} out of the grid", pos))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for v in row {
+                write!(f, "{}", v)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid = Grid::parse("ab\ncd\nef");
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid[Pos::new(2, 1)], 'f');
+        assert_eq!(grid.get(Pos::new(3, 0)), None);
+        assert_eq!(grid.position(|&c| c == 'd'), Some(Pos::new(1, 1)));
+    }
+
+    #[test]
+    fn test_try_parse_ragged() {
+        assert_eq!(
+            Grid::try_parse("abc\nde\nfgh"),
+            Err(RaggedRow {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(Grid::try_parse("ab\ncd"), Ok(Grid::parse("ab\ncd")));
+    }
+
+    #[test]
+    #[should_panic(expected = "row 1 has 2 cells, expected 3")]
+    fn test_from_rows_ragged() {
+        Grid::parse("abc\nde");
+    }
+
+    #[test]
+    fn test_from_rows_padded() {
+        let grid = Grid::from_rows_padded(vec![vec!['a'], vec!['b', 'c', 'd'], vec![]], '.');
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.to_string(), "a..\nbcd\n...\n");
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(grid.neighbors8(Pos::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8(Pos::new(0, 0)).count(), 3);
+        assert_eq!(
+            grid.neighbors4(Pos::new(0, 2)).collect::<Vec<Pos>>(),
+            vec![Pos::new(1, 2), Pos::new(0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_wrapping_offset() {
+        let grid = Grid::new(4, 3, 0);
+        assert_eq!(grid.offset(Pos::new(0, 0), (-1, -1)), None);
+        assert_eq!(
+            grid.wrapping_offset(Pos::new(0, 0), (-1, -1)),
+            Pos::new(2, 3)
+        );
+        assert_eq!(grid.wrapping_offset(Pos::new(2, 3), (1, 1)), Pos::new(0, 0));
+    }
+
+    #[test]
+    fn test_rows_columns_transposed() {
+        let grid = Grid::parse("abc\ndef");
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(
+            grid.columns()
+                .map(|c| c.collect::<String>())
+                .collect::<Vec<String>>(),
+            vec!["ad", "be", "cf"]
+        );
+
+        let t = grid.transposed();
+        assert_eq!(t.to_string(), "ad\nbe\ncf\n");
+        assert_eq!(t.transposed(), grid);
+    }
+
+    #[test]
+    #[should_panic(expected = "Column 3 out of the grid")]
+    fn test_column_out_of_bounds() {
+        let _ = Grid::parse("abc\ndef").column(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Column 0 out of the grid")]
+    fn test_column_of_empty_grid() {
+        let _ = Grid::<char>::new(0, 2, '.').column(0);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = Grid::parse("..\n..");
+        grid[Pos::new(0, 1)] = '@';
+        assert_eq!(grid.to_string(), ".@\n..\n");
+    }
+}
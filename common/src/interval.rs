@@ -0,0 +1,243 @@
+// Entiers discrets utilisables comme bornes d'intervalles inclusifs
+pub trait Discrete: Copy + Ord {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn succ(self) -> Option<Self>;
+    fn pred(self) -> Option<Self>;
+
+    // Nombre d'entiers dans [lo, hi] (lo <= hi) ; u128 car [0, u64::MAX] en compte 2^64
+    fn count(lo: Self, hi: Self) -> u128;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(
+            impl Discrete for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn count(lo: Self, hi: Self) -> u128 {
+                    (hi - lo) as u128 + 1
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize);
+
+// Ensemble d'entiers sous forme d'intervalles inclusifs triés, disjoints et non contigus :
+// la représentation est normalisée dès la construction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Discrete> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+}
+
+impl<T: Discrete> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        IntervalSet::from_ranges(iter)
+    }
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Les intervalles vides (lo > hi) sont ignorés
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut ranges: Vec<(T, T)> = ranges.into_iter().filter(|&(lo, hi)| lo <= hi).collect();
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (lo, hi) in ranges {
+            match merged.last_mut() {
+                // Chevauchement ou contiguïté ; si `last.1` vaut MAX tout ce qui suit est couvert
+                Some(last) if last.1.succ().is_none_or(|next| lo <= next) => {
+                    last.1 = last.1.max(hi)
+                }
+                _ => merged.push((lo, hi)),
+            }
+        }
+
+        IntervalSet { ranges: merged }
+    }
+
+    // Recherche dichotomique : O(log n)
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, hi)| hi < x);
+        self.ranges.get(i).is_some_and(|&(lo, _)| lo <= x)
+    }
+
+    // Nombre total d'entiers couverts
+    pub fn len(&self) -> u128 {
+        self.ranges.iter().map(|&(lo, hi)| T::count(lo, hi)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.ranges.iter().copied()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_ranges(self.iter().chain(other.iter()))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_lo, a_hi) = self.ranges[i];
+            let (b_lo, b_hi) = other.ranges[j];
+            let lo = a_lo.max(b_lo);
+            let hi = a_hi.min(b_hi);
+            if lo <= hi {
+                ranges.push((lo, hi));
+            }
+            if a_hi < b_hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    // Entiers de [lo, hi] absents de l'ensemble
+    pub fn complement(&self, lo: T, hi: T) -> Self {
+        let mut ranges = vec![];
+        // Premier entier pas encore traité (`None` : on a dépassé MAX)
+        let mut next = Some(lo);
+
+        for (r_lo, r_hi) in self.iter() {
+            let Some(start) = next else { break };
+            if r_lo > hi {
+                break;
+            }
+            if r_hi < start {
+                continue;
+            }
+            if r_lo > start {
+                // r_lo > start >= MIN : le prédécesseur existe
+                ranges.push((start, r_lo.pred().unwrap()));
+            }
+            next = r_hi.succ();
+        }
+
+        if let Some(start) = next {
+            if start <= hi {
+                ranges.push((start, hi));
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement(T::MIN, T::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[(u64, u64)]) -> IntervalSet<u64> {
+        IntervalSet::from_ranges(ranges.iter().copied())
+    }
+
+    #[test]
+    fn test_normalisation() {
+        let s = set(&[(16, 20), (3, 5), (10, 14), (12, 18), (6, 6), (30, 25)]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![(3, 6), (10, 20)]);
+        assert_eq!(s.len(), 4 + 11);
+    }
+
+    #[test]
+    fn test_bounds_of_the_type() {
+        let s = set(&[(0, 0), (0, 0), (1, 1)]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![(0, 1)]);
+        assert_eq!(s.len(), 2);
+
+        let s = set(&[(u64::MAX, u64::MAX), (0, u64::MAX - 1)]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![(0, u64::MAX)]);
+        assert_eq!(s.len(), 1 << 64);
+        assert!(s.contains(0) && s.contains(u64::MAX));
+        assert!(s.complement(0, u64::MAX).is_empty());
+
+        let s = set(&[(u64::MAX - 1, u64::MAX), (5, 5)]);
+        assert_eq!(s.complement(0, u64::MAX), set(&[(0, 4), (6, u64::MAX - 2)]));
+        assert_eq!(IntervalSet::<u64>::new().len(), 0);
+    }
+
+    #[test]
+    fn test_contains() {
+        let s = set(&[(3, 5), (10, 14), (16, 20), (12, 18)]);
+        let inside: Vec<u64> = (0..25).filter(|&x| s.contains(x)).collect();
+        assert_eq!(
+            inside,
+            vec![3, 4, 5, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20]
+        );
+        assert!(!IntervalSet::<u64>::new().contains(0));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (40, 40)]);
+
+        assert_eq!(a.union(&b), set(&[(0, 30), (40, 40)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25)]));
+        assert_eq!(a.difference(&b), set(&[(0, 4), (26, 30)]));
+        assert_eq!(b.difference(&a), set(&[(11, 19), (40, 40)]));
+        assert_eq!(a.complement(0, 35), set(&[(11, 19), (31, 35)]));
+        assert_eq!(a.complement(5, 25), set(&[(11, 19)]));
+        assert_eq!(a.complement(12, 15), set(&[(12, 15)]));
+        assert_eq!(a.complement(3, 8), IntervalSet::new());
+    }
+
+    #[test]
+    fn test_set_operations_match_brute_force() {
+        let a = set(&[(2, 4), (7, 7), (9, 15), (18, 19)]);
+        let b = set(&[(0, 2), (5, 9), (14, 16), (19, 19)]);
+        let members =
+            |s: &IntervalSet<u64>| (0..25).filter(|&x| s.contains(x)).collect::<Vec<u64>>();
+
+        for x in 0..25 {
+            assert_eq!(a.union(&b).contains(x), a.contains(x) || b.contains(x));
+            assert_eq!(
+                a.intersection(&b).contains(x),
+                a.contains(x) && b.contains(x)
+            );
+            assert_eq!(
+                a.difference(&b).contains(x),
+                a.contains(x) && !b.contains(x)
+            );
+            assert_eq!(
+                a.complement(1, 20).contains(x),
+                (1..=20).contains(&x) && !a.contains(x)
+            );
+        }
+        assert_eq!(a.union(&b).len(), members(&a.union(&b)).len() as u128);
+    }
+}
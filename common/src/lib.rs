@@ -0,0 +1,9 @@
+// Outils partagés entre les solutions
+
+pub mod args;
+pub mod grid;
+pub mod interval;
+
+pub use args::flag_value;
+pub use grid::{Grid, Pos, RaggedRow};
+pub use interval::IntervalSet;
@@ -1,7 +1,9 @@
+use common::flag_value;
+
 fn main() {
     let input = include_str!("../input.txt");
 
-    let splits = input.lines().map(|line| line.split_at(1)).collect::<Vec<(&str, &str)>>();
+    let splits = parse(input);
 
     let start = std::time::Instant::now();
     println!("Part 1: {}", part1(&splits));
@@ -10,6 +12,93 @@ fn main() {
     let start = std::time::Instant::now();
     println!("Part 2: {}", part2(&splits));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    // `--trace <fichier>` : export de chaque rotation (JSON si l'extension est .json, CSV sinon)
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = flag_value(&args, "--trace") {
+        let rotations = trace(&splits);
+        let content = if path.ends_with(".json") {
+            to_json(&rotations)
+        } else {
+            to_csv(&rotations)
+        };
+        std::fs::write(path, content).expect("Write failed");
+        println!("Trace: {} rotations written to {}", rotations.len(), path);
+    }
+}
+
+fn parse(input: &str) -> Vec<(&str, &str)> {
+    input.lines().map(|line| line.split_at(1)).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rotation {
+    index: usize,
+    dir: char,
+    dist: i32,
+    before: i32,
+    after: i32,
+    ends_on_zero: bool, // sémantique part1 : le cadran s'arrête sur 0
+    zero_clicks: i32,   // sémantique part2 : nombre de passages par 0 pendant la rotation
+}
+
+fn trace(splits: &[(&str, &str)]) -> Vec<Rotation> {
+    let mut index = 50;
+
+    splits
+        .iter()
+        .enumerate()
+        .map(|(i, (dir_str, dist_str))| {
+            let dir = dir_str.parse::<char>().expect("Parse failed");
+            let dist = dist_str.parse::<i32>().expect("Parse failed");
+            let before = index;
+
+            let zero_clicks = match dir {
+                'R' => (before + dist) / 100,
+                'L' if before == 0 => dist / 100,
+                'L' if dist >= before => (dist - before) / 100 + 1,
+                'L' => 0,
+                _ => panic!("Unexpected direction {dir}"),
+            };
+
+            let signed = if dir == 'L' { -dist } else { dist };
+            index = (before + signed).rem_euclid(100);
+
+            Rotation {
+                index: i,
+                dir,
+                dist,
+                before,
+                after: index,
+                ends_on_zero: index == 0,
+                zero_clicks,
+            }
+        })
+        .collect()
+}
+
+fn to_csv(rotations: &[Rotation]) -> String {
+    let mut out = String::from("index,direction,distance,before,after,ends_on_zero,zero_clicks\n");
+    for r in rotations {
+        out += &format!(
+            "{},{},{},{},{},{},{}\n",
+            r.index, r.dir, r.dist, r.before, r.after, r.ends_on_zero, r.zero_clicks
+        );
+    }
+    out
+}
+
+fn to_json(rotations: &[Rotation]) -> String {
+    let lines: Vec<String> = rotations
+        .iter()
+        .map(|r| {
+            format!(
+                "  {{\"index\": {}, \"direction\": \"{}\", \"distance\": {}, \"before\": {}, \"after\": {}, \"ends_on_zero\": {}, \"zero_clicks\": {}}}",
+                r.index, r.dir, r.dist, r.before, r.after, r.ends_on_zero, r.zero_clicks
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", lines.join(",\n"))
 }
 
 fn part1(splits: &[(&str, &str)]) -> i32 {
@@ -57,3 +146,43 @@ fn part2(splits: &[(&str, &str)]) -> i32 {
 
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part1_example() {
+        let splits = parse(include_str!("../example.txt"));
+        assert_eq!(part1(&splits), 3);
+    }
+
+    #[test]
+    fn test_part2_example() {
+        let splits = parse(include_str!("../example.txt"));
+        assert_eq!(part2(&splits), 6);
+    }
+
+    #[test]
+    fn test_trace_matches_parts() {
+        let splits = parse(include_str!("../example.txt"));
+        let rotations = trace(&splits);
+        let hits = rotations.iter().filter(|r| r.ends_on_zero).count();
+        let clicks: i32 = rotations.iter().map(|r| r.zero_clicks).sum();
+        assert_eq!(hits as i32, part1(&splits));
+        assert_eq!(clicks, part2(&splits));
+    }
+
+    #[test]
+    fn test_trace_csv() {
+        let splits = parse("L68\nR48\nL200\n");
+        let csv = to_csv(&trace(&splits));
+        assert_eq!(
+            csv,
+            "index,direction,distance,before,after,ends_on_zero,zero_clicks\n\
+             0,L,68,50,82,false,1\n\
+             1,R,48,82,30,false,1\n\
+             2,L,200,30,30,false,2\n"
+        );
+    }
+}
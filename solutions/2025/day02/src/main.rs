@@ -1,7 +1,13 @@
+use common::flag_value;
+
 fn main() {
     let input = include_str!("../input.txt");
 
-    let ranges = input.trim().split(',').collect::<Vec<&str>>();
+    // Par défaut les plages qui se chevauchent sont fusionnées pour ne compter chaque ID
+    // qu'une fois ; `--per-range` garde l'ancienne sémantique (somme plage par plage).
+    let args: Vec<String> = std::env::args().collect();
+    let mode = RangeMode::from_args(&args);
+    let ranges = mode.apply(parse(input));
 
     let start = std::time::Instant::now();
     println!("Part 1: {}", part1(&ranges));
@@ -10,75 +16,386 @@ fn main() {
     let start = std::time::Instant::now();
     println!("Part 2: {}", part2(&ranges));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    // Requête personnalisée : --exactly K, --at-least K, --at-most K ou --repeats K1,K2,...
+    // et `--radix R` (2 à 36) pour chercher les motifs répétés dans une autre base.
+    let radix =
+        flag_value(&args, "--radix").map_or(10, |r| r.parse::<u32>().expect("Invalid radix"));
+    assert!((2..=36).contains(&radix), "Radix must be between 2 and 36");
+
+    let spec = RepeatSpec::from_args(&args);
+    if spec.is_some() || radix != 10 {
+        let spec = spec.unwrap_or(RepeatSpec::AtLeast(2));
+        let start = std::time::Instant::now();
+        println!(
+            "{:?} (base {}): sum = {}, count = {}",
+            spec,
+            radix,
+            sum_invalid(&ranges, &spec, radix),
+            count_invalid(&ranges, &spec, radix)
+        );
+        println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    // `--check ID` : teste un ID isolé (écrit dans la base choisie)
+    if let Some(id_str) = flag_value(&args, "--check") {
+        let id = u128::from_str_radix(id_str, radix).expect("Invalid ID");
+        let spec = RepeatSpec::from_args(&args).unwrap_or(RepeatSpec::AtLeast(2));
+        println!(
+            "{} (base {}): invalid = {}",
+            id_str,
+            radix,
+            is_invalid(id, radix, &spec)
+        );
+    }
 }
 
-fn part1(ranges: &[&str]) -> i64 {
-    let mut res = 0;
+// Nombre de répétitions `k` du motif accepté pour qu'un ID soit invalide.
+// Un motif est toujours répété au moins 2 fois : `k = 1` n'est jamais énuméré.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RepeatSpec {
+    Exactly(u32),
+    AtLeast(u32),
+    AtMost(u32),
+    OneOf(Vec<u32>),
+}
+
+impl RepeatSpec {
+    fn accepts(&self, k: u32) -> bool {
+        match self {
+            RepeatSpec::Exactly(n) => k == *n,
+            RepeatSpec::AtLeast(n) => k >= *n,
+            RepeatSpec::AtMost(n) => k <= *n,
+            RepeatSpec::OneOf(ns) => ns.contains(&k),
+        }
+    }
 
-    for range in ranges {
-        let (id1_str, id2_str) = range.split_once('-').unwrap();
-        let id1 = id1_str.parse::<i64>().expect("Parsing error {id2_str}");
-        let id2 = id2_str.parse::<i64>().expect("Parsing error {id2_str}");
+    fn from_args(args: &[String]) -> Option<RepeatSpec> {
+        let value = |flag: &str| flag_value(args, flag);
+        let parse_k = |v: &str| v.parse::<u32>().expect("Invalid repeat count");
 
-        for id in id1..=id2 {
-            let id_str = id.to_string();
-            if id_str.len() % 2 == 0 {
-                let (start, end) = id_str.split_at(id_str.len() / 2);
-                if start == end {
-                    res += id_str.parse::<i64>().unwrap();
-                }
-            }
+        if let Some(v) = value("--exactly") {
+            Some(RepeatSpec::Exactly(parse_k(v)))
+        } else if let Some(v) = value("--at-least") {
+            Some(RepeatSpec::AtLeast(parse_k(v)))
+        } else if let Some(v) = value("--at-most") {
+            Some(RepeatSpec::AtMost(parse_k(v)))
+        } else {
+            value("--repeats").map(|v| RepeatSpec::OneOf(v.split(',').map(parse_k).collect()))
         }
     }
-    res
 }
 
-fn part2(ranges: &[&str]) -> i64 {
-    let mut res = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeMode {
+    PerRange,
+    Union,
+}
 
-    for range in ranges {
-        let (id1_str, id2_str) = range.split_once('-').unwrap();
-        let id1 = id1_str.parse::<i64>().expect("Parsing error {id2_str}");
-        let id2 = id2_str.parse::<i64>().expect("Parsing error {id2_str}");
+impl RangeMode {
+    fn from_args(args: &[String]) -> RangeMode {
+        if args.iter().any(|a| a == "--per-range") {
+            RangeMode::PerRange
+        } else {
+            RangeMode::Union
+        }
+    }
 
-        for id in id1..=id2 {
-            let id_str = id.to_string();
+    fn apply(self, ranges: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+        match self {
+            RangeMode::PerRange => ranges,
+            RangeMode::Union => merge_ranges(ranges),
+        }
+    }
+}
 
-            for cut in 1..=(id_str.len() / 2) {
-                if id_str.len() % cut == 0 {
-                    let parts : Vec<&str> = id_str.as_bytes()
-                        .chunks(cut)
-                        .map(|chunk| std::str::from_utf8(chunk).unwrap())
-                        .collect();
+// Trie et fusionne les plages qui se chevauchent ou se touchent (3-5 et 6-8 -> 3-8)
+fn merge_ranges(mut ranges: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+    ranges.sort_unstable();
 
-                    let first = parts.iter().next().unwrap();
-                    if parts.iter().all(|s| s == first) {
-                        res += id_str.parse::<i64>().unwrap();
-                        break
-                    }
-                }
+    let mut merged: Vec<(u128, u128)> = Vec::with_capacity(ranges.len());
+    for (lo, hi) in ranges {
+        match merged.last_mut() {
+            Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+
+    merged
+}
+
+fn parse(input: &str) -> Vec<(u128, u128)> {
+    input.trim().split(',').map(parse_range).collect()
+}
+
+fn parse_range(range: &str) -> (u128, u128) {
+    let (id1_str, id2_str) = range.split_once('-').unwrap();
+    let id1 = id1_str.trim().parse::<u128>().expect("Parsing error");
+    let id2 = id2_str.trim().parse::<u128>().expect("Parsing error");
+    (id1, id2)
+}
+
+fn digits(n: u128, radix: u32) -> u32 {
+    n.checked_ilog(radix as u128).unwrap_or(0) + 1
+}
+
+// Détection directe : l'écriture de `id` en base `radix` est-elle un motif répété `k` fois,
+// pour un `k` accepté par `spec` ?
+fn is_invalid(id: u128, radix: u32, spec: &RepeatSpec) -> bool {
+    let mut digits = vec![];
+    let mut n = id;
+    loop {
+        digits.push(n % radix as u128);
+        n /= radix as u128;
+        if n == 0 {
+            break;
+        }
+    }
+
+    let total = digits.len();
+    (1..=total / 2).any(|len| {
+        total.is_multiple_of(len)
+            && spec.accepts((total / len) as u32)
+            && digits.chunks(len).all(|chunk| chunk == &digits[..len])
+    })
+}
+
+// Multiplicateur qui répète un motif de `len` chiffres `repeats` fois en base `radix` :
+// 1 + r^len + r^(2·len) + ... (ex. base 10, len = 2, repeats = 3 -> 10101, et 12 * 10101 = 121212)
+fn repeat_multiplier(len: u32, repeats: u32, radix: u32) -> Option<u128> {
+    let shift = (radix as u128).checked_pow(len)?;
+    (0..repeats).try_fold(0u128, |acc, _| acc.checked_mul(shift)?.checked_add(1))
+}
+
+// Énumère directement les IDs de [lo, hi] dont l'écriture en base `radix` est un motif répété
+// `k` fois, pour chaque `k` accepté par `spec`. Un même ID peut s'écrire avec plusieurs `k` (1111 = 1×4 = 11×2),
+// d'où le dédoublonnage avant de rendre les IDs dans l'ordre croissant.
+fn invalid_ids(
+    (lo, hi): (u128, u128),
+    spec: &RepeatSpec,
+    radix: u32,
+) -> impl Iterator<Item = u128> {
+    let mut ids = vec![];
+    let base = radix as u128;
+
+    for total in digits(lo, radix)..=digits(hi, radix) {
+        for len in 1..=total / 2 {
+            let repeats = total / len;
+            if !total.is_multiple_of(len) || !spec.accepts(repeats) {
+                continue;
+            }
+
+            let Some(mult) = repeat_multiplier(len, repeats, radix) else {
+                continue;
+            };
+
+            // Le motif doit avoir exactement `len` chiffres (pas de zéro en tête)
+            let p_min = base.pow(len - 1).max(lo.div_ceil(mult));
+            let p_max = (base.pow(len) - 1).min(hi / mult);
+            if p_min <= p_max {
+                ids.extend((p_min..=p_max).map(|p| p * mult));
             }
         }
     }
 
-    res
+    ids.sort_unstable();
+    ids.dedup();
+    ids.into_iter()
+}
+
+fn sum_invalid(ranges: &[(u128, u128)], spec: &RepeatSpec, radix: u32) -> u128 {
+    ranges
+        .iter()
+        .map(|&range| invalid_ids(range, spec, radix).sum::<u128>())
+        .sum()
+}
+
+fn count_invalid(ranges: &[(u128, u128)], spec: &RepeatSpec, radix: u32) -> usize {
+    ranges
+        .iter()
+        .map(|&range| invalid_ids(range, spec, radix).count())
+        .sum()
+}
+
+fn part1(ranges: &[(u128, u128)]) -> u128 {
+    sum_invalid(ranges, &RepeatSpec::Exactly(2), 10)
+}
+
+fn part2(ranges: &[(u128, u128)]) -> u128 {
+    sum_invalid(ranges, &RepeatSpec::AtLeast(2), 10)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn split_args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
     #[test]
     fn test_part1_example() {
         let example_input = include_str!("../example.txt");
-        let ranges = example_input.trim().split(',').collect::<Vec<&str>>();
+        let ranges = parse(example_input);
         assert_eq!(part1(&ranges), 1227775554);
     }
 
     #[test]
     fn test_part2_example() {
         let example_input = include_str!("../example.txt");
-        let ranges = example_input.trim().split(',').collect::<Vec<&str>>();
+        let ranges = parse(example_input);
         assert_eq!(part2(&ranges), 4174379265);
     }
+
+    fn is_repeated_brute(id: u128, spec: &RepeatSpec) -> bool {
+        is_repeated_str(&id.to_string(), spec)
+    }
+
+    fn is_repeated_str(id_str: &str, spec: &RepeatSpec) -> bool {
+        let len = id_str.len();
+        (1..=len / 2).any(|cut| {
+            len.is_multiple_of(cut)
+                && spec.accepts((len / cut) as u32)
+                && id_str
+                    .as_bytes()
+                    .chunks(cut)
+                    .all(|c| c == &id_str.as_bytes()[..cut])
+        })
+    }
+
+    #[test]
+    fn test_invalid_ids_match_brute_force() {
+        let specs = [
+            RepeatSpec::Exactly(2),
+            RepeatSpec::Exactly(3),
+            RepeatSpec::AtLeast(2),
+            RepeatSpec::AtLeast(3),
+            RepeatSpec::AtMost(3),
+            RepeatSpec::OneOf(vec![2, 5]),
+        ];
+
+        for (lo, hi) in [(0, 12_000), (95, 115), (998_000, 1_012_000)] {
+            for spec in &specs {
+                let expected: Vec<u128> = (lo..=hi)
+                    .filter(|&id| is_repeated_brute(id, spec))
+                    .collect();
+                assert_eq!(
+                    invalid_ids((lo, hi), spec, 10).collect::<Vec<u128>>(),
+                    expected,
+                    "{spec:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_ids_huge_range() {
+        // Impossible à parcourir ID par ID, instantané par énumération
+        let ranges = [(1, 999_999_999_999)];
+        let spec = RepeatSpec::Exactly(2);
+        assert_eq!(
+            count_invalid(&ranges, &spec, 10),
+            9 + 90 + 900 + 9_000 + 90_000 + 900_000
+        );
+        assert_eq!(
+            invalid_ids(ranges[0], &spec, 10).last(),
+            Some(999_999_999_999)
+        );
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        let ranges = vec![(16, 20), (3, 5), (10, 14), (12, 18), (6, 6), (30, 30)];
+        assert_eq!(merge_ranges(ranges), vec![(3, 6), (10, 20), (30, 30)]);
+        assert_eq!(merge_ranges(vec![]), vec![]);
+        assert_eq!(
+            merge_ranges(vec![(0, u128::MAX), (5, 10)]),
+            vec![(0, u128::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_ranges() {
+        let ranges = parse("11-22,20-99,95-115");
+        // 22 et 99 sont comptés deux fois plage par plage
+        assert_eq!(
+            part1(&RangeMode::PerRange.apply(ranges.clone())),
+            495 + 22 + 99
+        );
+        assert_eq!(part1(&RangeMode::Union.apply(ranges)), 495);
+    }
+
+    #[test]
+    fn test_large_ids() {
+        // IDs de 24 chiffres, au-delà de u64
+        let ranges = parse("123456789012123456789012-123456789013123456789013");
+        assert_eq!(
+            invalid_ids(ranges[0], &RepeatSpec::Exactly(2), 10).collect::<Vec<u128>>(),
+            vec![123456789012123456789012, 123456789013123456789013]
+        );
+        assert_eq!(count_invalid(&ranges, &RepeatSpec::AtLeast(2), 10), 2);
+    }
+
+    #[test]
+    fn test_is_invalid_other_bases() {
+        // 0b1010 = "10" répété deux fois, 0xABAB = "AB" répété deux fois
+        assert!(is_invalid(0b1010, 2, &RepeatSpec::Exactly(2)));
+        assert!(is_invalid(0b111, 2, &RepeatSpec::Exactly(3)));
+        assert!(!is_invalid(0b1011, 2, &RepeatSpec::AtLeast(2)));
+        assert!(is_invalid(0xABAB, 16, &RepeatSpec::Exactly(2)));
+        assert!(!is_invalid(0xABAB, 10, &RepeatSpec::AtLeast(2)));
+
+        // Comparaison avec l'écriture fournie par `format!`
+        for id in 0..5_000u128 {
+            assert_eq!(
+                is_invalid(id, 2, &RepeatSpec::AtLeast(2)),
+                is_repeated_str(&format!("{id:b}"), &RepeatSpec::AtLeast(2))
+            );
+            assert_eq!(
+                is_invalid(id, 16, &RepeatSpec::AtLeast(2)),
+                is_repeated_str(&format!("{id:x}"), &RepeatSpec::AtLeast(2))
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_ids_every_base_match_brute_force() {
+        let specs = [
+            RepeatSpec::Exactly(2),
+            RepeatSpec::AtLeast(2),
+            RepeatSpec::OneOf(vec![3, 4]),
+        ];
+
+        for radix in 2..=36 {
+            for (lo, hi) in [(0, 3_000), (40_000, 41_000)] {
+                for spec in &specs {
+                    let expected: Vec<u128> = (lo..=hi)
+                        .filter(|&id| is_invalid(id, radix, spec))
+                        .collect();
+                    assert_eq!(
+                        invalid_ids((lo, hi), spec, radix).collect::<Vec<u128>>(),
+                        expected,
+                        "base {radix}, {spec:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_repeat_spec_from_args() {
+        assert_eq!(
+            RepeatSpec::from_args(&split_args("day02 --exactly 3")),
+            Some(RepeatSpec::Exactly(3))
+        );
+        assert_eq!(
+            RepeatSpec::from_args(&split_args("day02 --at-most 4")),
+            Some(RepeatSpec::AtMost(4))
+        );
+        assert_eq!(
+            RepeatSpec::from_args(&split_args("day02 --repeats 2,3")),
+            Some(RepeatSpec::OneOf(vec![2, 3]))
+        );
+        assert_eq!(RepeatSpec::from_args(&split_args("day02")), None);
+    }
 }
@@ -1,14 +1,60 @@
+use common::flag_value;
+use std::fmt;
+
 fn main() {
     let input = include_str!("../input.txt");
     let banks = parse(input);
 
     let start = std::time::Instant::now();
-    println!("Part 1: {}", part1(&banks));
+    match part1(&banks) {
+        Ok(res) => println!("Part 1: {}", res),
+        Err(e) => eprintln!("Part 1: {}", e),
+    }
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
 
     let start = std::time::Instant::now();
-    println!("Part 2: {}", part2(&banks));
+    match part2(&banks) {
+        Ok(res) => println!("Part 2: {}", res),
+        Err(e) => eprintln!("Part 2: {}", e),
+    }
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    // Variante : `--turn N [--smallest] [--min-gap G]`
+    let args: Vec<String> = std::env::args().collect();
+    let custom = Rules::from_args(&args);
+    if let Some(rules) = custom {
+        let start = std::time::Instant::now();
+        match solve(&banks, &rules) {
+            Ok(total) => println!("{:?}: {}", rules, total),
+            Err(e) => eprintln!("{:?}: {}", rules, e),
+        }
+        println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    // `--explain` : affiche chaque banque avec les batteries retenues en surbrillance
+    // (entre crochets avec `--no-color`)
+    if args.iter().any(|a| a == "--explain") {
+        let color = !args.iter().any(|a| a == "--no-color");
+        let all_rules = match custom {
+            Some(rules) => vec![rules],
+            None => vec![Rules::largest(2), Rules::largest(12)],
+        };
+        for rules in all_rules {
+            println!();
+            println!("{:?}", rules);
+            for bank in &banks {
+                match select(bank, &rules) {
+                    Some(selection) => {
+                        println!("{} -> {}", highlight(bank, &selection, color), selection)
+                    }
+                    None => println!(
+                        "{} -> (too short)",
+                        highlight(bank, &Selection::default(), color)
+                    ),
+                }
+            }
+        }
+    }
 }
 
 fn parse(input: &str) -> Vec<Vec<u8>> {
@@ -22,59 +68,435 @@ fn parse(input: &str) -> Vec<Vec<u8>> {
         .collect::<Vec<Vec<u8>>>()
 }
 
-fn solve(banks: &[Vec<u8>], to_turn: usize) -> i64 {
-    let mut res = 0;
-
-    for bank in banks {
-        let mut max = vec![0u8; to_turn];
-        let mut k = 0;
-        for i in 0..max.len() {
-            let to_fill = max.len() - i;
-            let bank_rest = bank.len() - k;
-            let end = k + bank_rest - to_fill + 1;
-            for j in k..end {
-                if bank[j] == 9 {
-                    max[i] = 9;
-                    k = j + 1;
-                    break;
-                }
+// Batteries retenues dans une banque : positions (croissantes) et chiffres correspondants
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Selection {
+    indices: Vec<usize>,
+    digits: Vec<u8>,
+}
 
-                if bank[j] > max[i] {
-                    max[i] = bank[j];
-                    k = j + 1;
-                }
+impl Selection {
+    // `None` si la valeur dépasse u128 (plus de 38 chiffres) : passer alors par `to_string`
+    fn value(&self) -> Option<u128> {
+        self.digits
+            .iter()
+            .try_fold(0u128, |acc, &d| acc.checked_mul(10)?.checked_add(d as u128))
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.digits {
+            write!(f, "{}", d)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Goal {
+    Largest,
+    Smallest,
+}
+
+// Règles de sélection : nombre de batteries à allumer, valeur recherchée,
+// et nombre minimal de batteries laissées éteintes entre deux batteries retenues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rules {
+    to_turn: usize,
+    goal: Goal,
+    min_gap: usize,
+}
+
+impl Rules {
+    fn largest(to_turn: usize) -> Rules {
+        Rules {
+            to_turn,
+            goal: Goal::Largest,
+            min_gap: 0,
+        }
+    }
+
+    // `--turn N [--smallest] [--min-gap G]`
+    fn from_args(args: &[String]) -> Option<Rules> {
+        let value = |flag: &str| {
+            flag_value(args, flag).map(|v| v.parse::<usize>().expect("Invalid number"))
+        };
+
+        let to_turn = value("--turn")?;
+        let goal = if args.iter().any(|a| a == "--smallest") {
+            Goal::Smallest
+        } else {
+            Goal::Largest
+        };
+
+        Some(Rules {
+            to_turn,
+            goal,
+            min_gap: value("--min-gap").unwrap_or(0),
+        })
+    }
+
+    // Longueur minimale d'une banque pour ces règles, `None` si elle dépasse usize
+    // (`--turn` et `--min-gap` viennent de la ligne de commande)
+    fn needed(&self) -> Option<usize> {
+        match self.to_turn {
+            0 => Some(0),
+            k => (k - 1)
+                .checked_mul(self.min_gap.checked_add(1)?)?
+                .checked_add(1),
+        }
+    }
+
+    // `a` est-il strictement préférable à `b` en tête de sélection ?
+    fn better(&self, a: u8, b: u8) -> bool {
+        match self.goal {
+            Goal::Largest => a > b,
+            Goal::Smallest => a < b,
+        }
+    }
+}
+
+// Meilleure sous-suite de `rules.to_turn` chiffres, `None` si la banque est trop courte.
+fn select(bank: &[u8], rules: &Rules) -> Option<Selection> {
+    if rules.needed().is_none_or(|needed| needed > bank.len()) {
+        return None;
+    }
+
+    let indices = if rules.min_gap == 0 {
+        select_stack(bank, rules)
+    } else {
+        select_spaced(bank, rules)
+    };
+
+    Some(Selection {
+        digits: indices.iter().map(|&i| bank[i]).collect(),
+        indices,
+    })
+}
+
+// Sans contrainte d'écart, en O(n) avec une pile monotone : on peut écarter
+// `bank.len() - to_turn` chiffres, et tant qu'il en reste à écarter, un chiffre
+// moins bon que celui qui arrive n'a pas sa place devant lui.
+fn select_stack(bank: &[u8], rules: &Rules) -> Vec<usize> {
+    let mut to_drop = bank.len() - rules.to_turn;
+    let mut stack: Vec<usize> = Vec::with_capacity(bank.len());
+
+    for (i, &d) in bank.iter().enumerate() {
+        while to_drop > 0 && stack.last().is_some_and(|&top| rules.better(d, bank[top])) {
+            stack.pop();
+            to_drop -= 1;
+        }
+        stack.push(i);
+    }
+    stack.truncate(rules.to_turn);
+
+    stack
+}
+
+// Avec un écart minimal, glouton en O(n·k) : chaque chiffre est le meilleur de la fenêtre
+// qui laisse encore la place aux suivants, et à égalité le plus à gauche laisse le plus de choix.
+fn select_spaced(bank: &[u8], rules: &Rules) -> Vec<usize> {
+    let step = rules.min_gap + 1;
+    let mut indices = Vec::with_capacity(rules.to_turn);
+    let mut from = 0;
+
+    for remaining in (0..rules.to_turn).rev() {
+        let last = bank.len() - 1 - remaining * step;
+        let mut best = from;
+        for j in from + 1..=last {
+            if rules.better(bank[j], bank[best]) {
+                best = j;
             }
         }
-        let imax = max.iter().fold(0, |acc, &d| acc * 10 + d as i64);
-        res += imax;
+        indices.push(best);
+        from = best + step;
     }
 
-    res
+    indices
 }
 
-fn part1(banks: &[Vec<u8>]) -> i64 {
-    solve(banks, 2)
+fn highlight(bank: &[u8], selection: &Selection, color: bool) -> String {
+    let mut selected = selection.indices.iter().peekable();
+    let mut out = String::new();
+
+    for (i, d) in bank.iter().enumerate() {
+        if selected.next_if_eq(&&i).is_some() {
+            if color {
+                out += &format!("\x1b[1;32m{}\x1b[0m", d);
+            } else {
+                out += &format!("[{}]", d);
+            }
+        } else {
+            out += &d.to_string();
+        }
+    }
+
+    out
 }
 
-fn part2(banks: &[Vec<u8>]) -> i64 {
-    solve(banks, 12)
+// Somme de deux entiers positifs écrits en décimal
+fn add_decimal(a: &str, b: &str) -> String {
+    let (mut a, mut b) = (a.bytes().rev(), b.bytes().rev());
+    let mut digits = vec![];
+    let mut carry = 0;
+
+    loop {
+        let (x, y) = (a.next(), b.next());
+        if x.is_none() && y.is_none() {
+            break;
+        }
+        let sum = x.map_or(0, |d| d - b'0') + y.map_or(0, |d| d - b'0') + carry;
+        digits.push(b'0' + sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        digits.push(b'0' + carry);
+    }
+
+    // Zéros de tête (sélections commençant par 0), en gardant au moins un chiffre
+    while digits.len() > 1 && digits.last() == Some(&b'0') {
+        digits.pop();
+    }
+    digits.iter().rev().map(|&d| d as char).collect()
+}
+
+// Banque (numérotée à partir de 1) trop courte pour les règles demandées
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BankTooShort {
+    bank: usize,
+    len: usize,
+    needed: Option<usize>,
+}
+
+impl fmt::Display for BankTooShort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bank {} has {} batteries, ", self.bank, self.len)?;
+        match self.needed {
+            Some(needed) => write!(f, "these rules need at least {}", needed),
+            None => write!(f, "these rules need more than {}", usize::MAX),
+        }
+    }
+}
+
+// Somme des joltages en décimal : en u128 tant que ça tient, puis chiffre par chiffre
+// à partir de l'affichage des sélections qui dépassent.
+fn solve(banks: &[Vec<u8>], rules: &Rules) -> Result<String, BankTooShort> {
+    let mut small = 0u128;
+    let mut big = String::from("0");
+
+    for (i, bank) in banks.iter().enumerate() {
+        let selection = select(bank, rules).ok_or(BankTooShort {
+            bank: i + 1,
+            len: bank.len(),
+            needed: rules.needed(),
+        })?;
+        match selection.value().and_then(|v| small.checked_add(v)) {
+            Some(sum) => small = sum,
+            None => big = add_decimal(&big, &selection.to_string()),
+        }
+    }
+
+    Ok(add_decimal(&big, &small.to_string()))
+}
+
+fn part1(banks: &[Vec<u8>]) -> Result<String, BankTooShort> {
+    solve(banks, &Rules::largest(2))
+}
+
+fn part2(banks: &[Vec<u8>]) -> Result<String, BankTooShort> {
+    solve(banks, &Rules::largest(12))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn split_args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
     #[test]
     fn test_part1_example() {
         let input = include_str!("../example.txt");
         let banks = parse(input);
-        assert_eq!(part1(&banks), 357);
+        assert_eq!(part1(&banks).unwrap(), "357");
     }
 
     #[test]
     fn test_part2_example() {
         let input = include_str!("../example.txt");
         let banks = parse(input);
-        assert_eq!(part2(&banks), 3121910778619);
+        assert_eq!(part2(&banks).unwrap(), "3121910778619");
+    }
+
+    // Recherche exhaustive de la meilleure sous-suite respectant `rules`
+    fn brute_force(bank: &[u8], rules: &Rules) -> Option<Vec<u8>> {
+        let n = bank.len();
+        let candidates = (0u32..1 << n)
+            .filter(|mask| mask.count_ones() as usize == rules.to_turn)
+            .map(|mask| {
+                (0..n)
+                    .filter(|i| mask >> i & 1 == 1)
+                    .collect::<Vec<usize>>()
+            })
+            .filter(|idx| idx.windows(2).all(|w| w[1] - w[0] > rules.min_gap))
+            .map(|idx| idx.iter().map(|&i| bank[i]).collect::<Vec<u8>>());
+
+        match rules.goal {
+            Goal::Largest => candidates.max(),
+            Goal::Smallest => candidates.min(),
+        }
+    }
+
+    #[test]
+    fn test_select_indices() {
+        let bank = parse("818181911112111").remove(0);
+        let selection = select(&bank, &Rules::largest(2)).unwrap();
+        assert_eq!(selection.indices, vec![6, 11]);
+        assert_eq!(selection.value(), Some(92));
+        for (&i, &d) in selection.indices.iter().zip(&selection.digits) {
+            assert_eq!(bank[i], d);
+        }
+    }
+
+    #[test]
+    fn test_highlight() {
+        let bank = parse("234234234234278").remove(0);
+        let selection = select(&bank, &Rules::largest(2)).unwrap();
+        assert_eq!(highlight(&bank, &selection, false), "2342342342342[7][8]");
+        assert_eq!(
+            highlight(&bank, &select(&bank, &Rules::largest(12)).unwrap(), false),
+            "23[4]2[3][4][2][3][4][2][3][4][2][7][8]"
+        );
+        assert_eq!(
+            highlight(
+                &bank[..3],
+                &select(&bank[..3], &Rules::largest(1)).unwrap(),
+                true
+            ),
+            "23\x1b[1;32m4\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_select_matches_brute_force() {
+        let banks = parse("3141592653\n2718281828\n1111111111\n9876543210\n1020304050");
+        for bank in &banks {
+            for to_turn in 0..=bank.len() {
+                for goal in [Goal::Largest, Goal::Smallest] {
+                    for min_gap in 0..4 {
+                        let rules = Rules {
+                            to_turn,
+                            goal,
+                            min_gap,
+                        };
+                        let selection = select(bank, &rules);
+                        assert_eq!(
+                            selection.as_ref().map(|s| s.digits.clone()),
+                            brute_force(bank, &rules),
+                            "{bank:?} {rules:?}"
+                        );
+                        if let Some(selection) = selection {
+                            assert!(selection.indices.windows(2).all(|w| w[1] - w[0] > min_gap));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rules_from_args() {
+        assert_eq!(Rules::from_args(&split_args("day03 --explain")), None);
+        assert_eq!(
+            Rules::from_args(&split_args("day03 --turn 3 --smallest --min-gap 1")),
+            Some(Rules {
+                to_turn: 3,
+                goal: Goal::Smallest,
+                min_gap: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_long_selection() {
+        let bank: Vec<u8> = (0..60).map(|i| (i * 7 % 10) as u8).collect();
+        let selection = select(&bank, &Rules::largest(40)).unwrap();
+        assert_eq!(selection.value(), None);
+        assert_eq!(selection.to_string().len(), 40);
+        assert_eq!(
+            selection.to_string(),
+            "9974852963074185296307418529630741852963"
+        );
+    }
+
+    #[test]
+    fn test_add_decimal() {
+        assert_eq!(add_decimal("0", "0"), "0");
+        assert_eq!(add_decimal("999", "1"), "1000");
+        assert_eq!(add_decimal("0042", "8"), "50");
+        assert_eq!(
+            add_decimal(&u128::MAX.to_string(), "1"),
+            "340282366920938463463374607431768211456"
+        );
+    }
+
+    #[test]
+    fn test_solve_beyond_u128() {
+        // Deux banques de 45 chiffres : chaque joltage de 40 chiffres dépasse u128
+        let banks = parse(&format!("{}\n{}", "9".repeat(45), "1".repeat(45)));
+        assert_eq!(
+            solve(&banks, &Rules::largest(40)),
+            Ok(format!("1{}0", "1".repeat(39)))
+        );
+
+        // Somme qui ne dépasse u128 qu'à la dernière banque
+        let banks = parse(&format!("{}\n{}", "9".repeat(38), "9".repeat(38)));
+        assert_eq!(
+            solve(&banks, &Rules::largest(38)),
+            Ok(add_decimal(&"9".repeat(38), &"9".repeat(38)))
+        );
+    }
+
+    #[test]
+    fn test_solve_bank_too_short() {
+        let banks = parse("12345\n123\n12345");
+        let rules = Rules {
+            to_turn: 3,
+            goal: Goal::Largest,
+            min_gap: 1,
+        };
+        let err = solve(&banks, &rules).unwrap_err();
+        assert_eq!(
+            err,
+            BankTooShort {
+                bank: 2,
+                len: 3,
+                needed: Some(5)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "bank 2 has 3 batteries, these rules need at least 5"
+        );
+
+        // Longueur requise qui dépasse usize : trop court plutôt qu'un débordement
+        for rules in [
+            Rules {
+                to_turn: usize::MAX,
+                goal: Goal::Largest,
+                min_gap: 2,
+            },
+            Rules {
+                to_turn: 3,
+                goal: Goal::Largest,
+                min_gap: usize::MAX,
+            },
+        ] {
+            assert_eq!(rules.needed(), None);
+            assert_eq!(select(&banks[0], &rules), None);
+            assert_eq!(solve(&banks, &rules).unwrap_err().needed, None);
+        }
     }
 }
@@ -1,120 +1,505 @@
-use std::collections::HashMap;
+use common::grid::{DIRS4, DIRS8};
+use common::{flag_value, Grid, Pos};
+use std::collections::VecDeque;
 use std::fmt;
 
-fn get_neighbors<T: Copy>(matrix: &[Vec<T>], row: usize, col: usize) -> HashMap<&'static str, T> {
-    let rows = matrix.len() as isize;
-    let cols = matrix[0].len() as isize;
-
-    let directions: [(&str, (isize, isize)); 8] = [
-        ("N", (-1, 0)),
-        ("NE", (-1, 1)),
-        ("E", (0, 1)),
-        ("SE", (1, 1)),
-        ("S", (1, 0)),
-        ("SW", (1, -1)),
-        ("W", (0, -1)),
-        ("NW", (-1, -1)),
-    ];
-
-    let mut neighbors = HashMap::new();
-
-    for (dir, (dr, dc)) in directions {
-        let nr = row as isize + dr;
-        let nc = col as isize + dc;
-
-        if nr >= 0 && nr < rows && nc >= 0 && nc < cols {
-            neighbors.insert(dir, matrix[nr as usize][nc as usize]);
-        }
+struct Diagram {
+    grid: Grid<char>,
+}
+
+impl fmt::Display for Diagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.grid)
     }
+}
 
-    neighbors
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Neighborhood {
+    Moore,
+    VonNeumann,
+    Custom(Vec<(isize, isize)>),
 }
 
-struct Diagram {
-    grid: Vec<Vec<char>>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Topology {
+    Bounded,
+    Torus,
 }
 
-impl fmt::Display for Diagram {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in &self.grid {
-            writeln!(f, "{:?}", row)?;
+// Règle d'accès : un rouleau est accessible s'il a moins de `threshold` rouleaux
+// parmi ses voisins
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules {
+    threshold: usize,
+    neighborhood: Neighborhood,
+    topology: Topology,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            threshold: 4,
+            neighborhood: Neighborhood::Moore,
+            topology: Topology::Bounded,
+        }
+    }
+}
+
+impl Rules {
+    // `--threshold N`, `--neighborhood moore|von-neumann|dr:dc,dr:dc,...`, `--torus`
+    fn from_args(args: &[String]) -> Rules {
+        let value = |flag: &str| flag_value(args, flag);
+
+        let neighborhood = match value("--neighborhood") {
+            None | Some("moore") => Neighborhood::Moore,
+            Some("von-neumann") => Neighborhood::VonNeumann,
+            Some(offsets) => Neighborhood::Custom(
+                offsets
+                    .split(',')
+                    .map(|o| {
+                        let (dr, dc) = o.split_once(':').expect("Offsets are dr:dc");
+                        (
+                            dr.parse().expect("Invalid offset"),
+                            dc.parse().expect("Invalid offset"),
+                        )
+                    })
+                    .collect(),
+            ),
+        };
+
+        Rules {
+            threshold: value("--threshold").map_or(4, |v| v.parse().expect("Invalid threshold")),
+            neighborhood,
+            topology: if args.iter().any(|a| a == "--torus") {
+                Topology::Torus
+            } else {
+                Topology::Bounded
+            },
+        }
+    }
+
+    fn offsets(&self) -> &[(isize, isize)] {
+        match &self.neighborhood {
+            Neighborhood::Moore => &DIRS8,
+            Neighborhood::VonNeumann => &DIRS4,
+            Neighborhood::Custom(offsets) => offsets,
+        }
+    }
+
+    // Sur un tore, un même voisin peut apparaître plusieurs fois (petites grilles) :
+    // il compte alors autant de fois.
+    fn neighbors(&self, grid: &Grid<char>, pos: Pos) -> Vec<Pos> {
+        self.shifted(grid, pos, 1)
+    }
+
+    // Cases dont `pos` est un voisin. Identique à `neighbors` pour un voisinage symétrique,
+    // mais un voisinage personnalisé peut ne pas l'être.
+    fn dependents(&self, grid: &Grid<char>, pos: Pos) -> Vec<Pos> {
+        self.shifted(grid, pos, -1)
+    }
+
+    fn shifted(&self, grid: &Grid<char>, pos: Pos, sign: isize) -> Vec<Pos> {
+        let offsets = self
+            .offsets()
+            .iter()
+            .map(|&(dr, dc)| (sign * dr, sign * dc));
+        match self.topology {
+            Topology::Bounded => offsets.filter_map(|d| grid.offset(pos, d)).collect(),
+            Topology::Torus => offsets.map(|d| grid.wrapping_offset(pos, d)).collect(),
         }
-        Ok(())
     }
 }
 
 fn main() {
     let input = include_str!("../input.txt");
-    let mut diagram = Diagram { grid: parse(input) };
+    let diagram = Diagram { grid: parse(input) };
+
+    let args: Vec<String> = std::env::args().collect();
+    let rules = Rules::from_args(&args);
 
     let start = std::time::Instant::now();
-    println!("Part 1: {}", part1(&diagram));
+    println!("Part 1: {}", part1(&diagram, &rules));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
 
+    let grid = &diagram.grid;
+
     let start = std::time::Instant::now();
-    println!("Part 2: {}", part2(&mut diagram));
+    let rounds = removal_rounds(grid, &rules);
+    println!("Part 2: {}", part2(&rounds));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    let stable_after = rounds.iter().filter_map(|(_, r)| *r).max().unwrap_or(0);
+    println!("Rounds until stable: {}", stable_after);
+
+    // `--rounds <fichier>` : carte des tours de retrait (CSV si l'extension est .csv)
+    if let Some(path) = flag_value(&args, "--rounds") {
+        let content = if path.ends_with(".csv") {
+            rounds_csv(grid, &rounds)
+        } else {
+            rounds_map(grid, &rounds)
+        };
+        std::fs::write(path, content).expect("Write failed");
+    }
+
+    // `--frames <dossier>` : une image PPM par tour de retrait, plus les étapes en ASCII
+    if let Some(dir) = flag_value(&args, "--frames") {
+        write_frames(dir, grid, &rounds);
+        println!("Frames: {} written to {}", stable_after + 1, dir);
+    }
 }
 
-fn parse(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|line| line.chars().collect()).collect()
+fn parse(input: &str) -> Grid<char> {
+    Grid::parse(input)
 }
 
-fn part1(diagram: &Diagram) -> usize {
-    let mut res = 0;
-    for i in 0..diagram.grid.len() {
-        for j in 0..diagram.grid[i].len() {
-            if diagram.grid[i][j] == '@' {
-                let neighbors = get_neighbors(&diagram.grid, i, j);
-                let nrolls = neighbors.values().filter(|&&v| v == '@').count();
-                if nrolls < 4 {
-                    res += 1;
-                }
+fn count_rolls_around(grid: &Grid<char>, pos: Pos, rules: &Rules) -> usize {
+    rules
+        .neighbors(grid, pos)
+        .into_iter()
+        .filter(|&n| grid[n] == '@')
+        .count()
+}
+
+fn part1(diagram: &Diagram, rules: &Rules) -> usize {
+    diagram
+        .grid
+        .positions()
+        .filter(|&pos| {
+            diagram.grid[pos] == '@'
+                && count_rolls_around(&diagram.grid, pos, rules) < rules.threshold
+        })
+        .count()
+}
+
+// Tour de retrait de chaque rouleau (`None` : case vide ou rouleau jamais retiré).
+// Les tours sont synchrones : au tour 1 partent les rouleaux accessibles au départ,
+// au tour r + 1 ceux qui deviennent accessibles une fois les tours 1..=r retirés.
+//
+// Épluchage par file de travail : le nombre de rouleaux voisins est calculé une fois,
+// puis seuls les voisins d'un rouleau retiré sont réexaminés (O(cellules) au total).
+// La file est FIFO, donc tout le tour r est traité avant le tour r + 1.
+fn removal_rounds(grid: &Grid<char>, rules: &Rules) -> Grid<Option<usize>> {
+    let mut counts = Grid::new(grid.width(), grid.height(), 0);
+    let mut rounds = Grid::new(grid.width(), grid.height(), None);
+    let mut queue = VecDeque::new();
+
+    for pos in grid.positions() {
+        if grid[pos] == '@' {
+            counts[pos] = count_rolls_around(grid, pos, rules);
+            if counts[pos] < rules.threshold {
+                rounds[pos] = Some(1);
+                queue.push_back(pos);
             }
         }
     }
-    res
-}
-
-fn part2(diagram: &mut Diagram) -> usize {
-    let mut res = 0;
-    loop {
-        let mut found = false;
-        for i in 0..diagram.grid.len() {
-            for j in 0..diagram.grid[i].len() {
-                if diagram.grid[i][j] == '@' {
-                    let neighbors = get_neighbors(&diagram.grid, i, j);
-                    let nrolls = neighbors.values().filter(|&&v| v == '@').count();
-                    if nrolls < 4 {
-                        diagram.grid[i][j] = 'x';
-                        res += 1;
-                        found = true;
-                    }
+
+    // Un rouleau reçoit son tour dès sa mise en file ; les cases qui le comptent parmi
+    // leurs voisins ne sont décomptées qu'au moment où il est traité.
+    while let Some(pos) = queue.pop_front() {
+        let round = rounds[pos].unwrap();
+        for n in rules.dependents(grid, pos) {
+            if grid[n] == '@' && rounds[n].is_none() {
+                counts[n] -= 1;
+                // `rounds[n]` est rempli dès la mise en file : un rouleau n'y entre qu'une fois
+                if counts[n] < rules.threshold {
+                    rounds[n] = Some(round + 1);
+                    queue.push_back(n);
                 }
             }
         }
-        if !found {
-            break;
+    }
+
+    rounds
+}
+
+// `rounds` : résultat de `removal_rounds` sur la grille du diagramme
+// Rouleaux retirés à un tour ou un autre
+fn part2(rounds: &Grid<Option<usize>>) -> usize {
+    rounds.iter().filter(|(_, round)| round.is_some()).count()
+}
+
+// Carte des tours : '.' case vide, '@' rouleau jamais retiré, puis 1-9, a-z pour les
+// tours 1 à 35 et '+' au-delà.
+fn rounds_map(grid: &Grid<char>, rounds: &Grid<Option<usize>>) -> String {
+    frame_ascii(grid, rounds, usize::MAX)
+}
+
+// État après le tour `upto` : les rouleaux retirés jusque-là portent leur tour,
+// les autres sont encore '@'.
+fn frame_ascii(grid: &Grid<char>, rounds: &Grid<Option<usize>>, upto: usize) -> String {
+    let cells = Grid::from_rows(
+        grid.rows()
+            .zip(rounds.rows())
+            .map(|(row, row_rounds)| {
+                row.iter()
+                    .zip(row_rounds)
+                    .map(|(&c, round)| match (c, round) {
+                        ('@', Some(r)) if *r <= upto => {
+                            char::from_digit(*r as u32, 36).unwrap_or('+')
+                        }
+                        ('@', _) => '@',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect(),
+    );
+    cells.to_string()
+}
+
+// Dégradé du rouge (premier tour) au bleu (dernier tour)
+fn round_color(round: usize, last: usize) -> [u8; 3] {
+    let t = if last > 1 {
+        (round - 1) as f64 / (last - 1) as f64
+    } else {
+        0.0
+    };
+    [
+        (230.0 * (1.0 - t)) as u8 + 20,
+        (160.0 * (1.0 - (2.0 * t - 1.0).abs())) as u8 + 20,
+        (230.0 * t) as u8 + 20,
+    ]
+}
+
+// Image PPM binaire (P6) de l'état après le tour `upto`, chaque case faisant `scale` pixels
+fn frame_ppm(
+    grid: &Grid<char>,
+    rounds: &Grid<Option<usize>>,
+    upto: usize,
+    last: usize,
+    scale: usize,
+) -> Vec<u8> {
+    let mut out = format!(
+        "P6\n{} {}\n255\n",
+        grid.width() * scale,
+        grid.height() * scale
+    )
+    .into_bytes();
+
+    for (row, row_rounds) in grid.rows().zip(rounds.rows()) {
+        let line: Vec<u8> = row
+            .iter()
+            .zip(row_rounds)
+            .flat_map(|(&c, round)| {
+                let color = match (c, round) {
+                    ('@', Some(r)) if *r <= upto => round_color(*r, last),
+                    ('@', _) => [240, 240, 240],
+                    _ => [30, 30, 30],
+                };
+                color.repeat(scale)
+            })
+            .collect();
+        for _ in 0..scale {
+            out.extend_from_slice(&line);
         }
     }
-    res
+
+    out
+}
+
+// Une image par tour (frame_000.ppm = état initial) et toutes les étapes en ASCII
+// dans frames.txt
+fn write_frames(dir: &str, grid: &Grid<char>, rounds: &Grid<Option<usize>>) {
+    let last = rounds.iter().filter_map(|(_, r)| *r).max().unwrap_or(0);
+    std::fs::create_dir_all(dir).expect("Cannot create frames directory");
+
+    let mut ascii = String::new();
+    for upto in 0..=last {
+        let path = format!("{}/frame_{:03}.ppm", dir, upto);
+        std::fs::write(path, frame_ppm(grid, rounds, upto, last, 4)).expect("Write failed");
+
+        ascii += &format!("Round {}\n", upto);
+        ascii += &frame_ascii(grid, rounds, upto);
+        ascii += "\n";
+    }
+    std::fs::write(format!("{}/frames.txt", dir), ascii).expect("Write failed");
+}
+
+// Même carte en CSV : numéro du tour, "never" pour un rouleau jamais retiré, vide sinon
+fn rounds_csv(grid: &Grid<char>, rounds: &Grid<Option<usize>>) -> String {
+    let mut out = String::new();
+
+    for (row, row_rounds) in grid.rows().zip(rounds.rows()) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(row_rounds)
+            .map(|(&c, round)| match (c, round) {
+                ('@', Some(r)) => r.to_string(),
+                ('@', None) => "never".to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        out += &cells.join(",");
+        out += "\n";
+    }
+
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn split_args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
     #[test]
     fn test_part1_example() {
         let input = include_str!("../example.txt");
         let diagram = Diagram { grid: parse(input) };
-        assert_eq!(part1(&diagram), 13);
+        assert_eq!(part1(&diagram, &Rules::default()), 13);
     }
 
     #[test]
     fn test_part2_example() {
         let input = include_str!("../example.txt");
-        let mut diagram = Diagram { grid: parse(input) };
-        assert_eq!(part2(&mut diagram), 43);
+        let rounds = removal_rounds(&parse(input), &Rules::default());
+        assert_eq!(part2(&rounds), 43);
+    }
+
+    #[test]
+    fn test_frames() {
+        let grid = parse(include_str!("../example.txt"));
+        let rounds = removal_rounds(&grid, &Rules::default());
+
+        // Avant le premier tour on retrouve la grille de départ
+        assert_eq!(frame_ascii(&grid, &rounds, 0), grid.to_string());
+        assert_eq!(frame_ascii(&grid, &rounds, 9), rounds_map(&grid, &rounds));
+        assert_eq!(
+            frame_ascii(&grid, &rounds, 1).lines().nth(1),
+            Some("1@@.@.@.@@")
+        );
+
+        let ppm = frame_ppm(&grid, &rounds, 3, 9, 2);
+        let header = b"P6\n20 20\n255\n";
+        assert!(ppm.starts_with(header));
+        assert_eq!(ppm.len(), header.len() + 20 * 20 * 3);
+
+        assert_eq!(round_color(1, 9), [250, 20, 20]);
+        assert_eq!(round_color(9, 9), [20, 20, 250]);
+    }
+
+    // Ancienne version : balayages complets jusqu'à stabilité
+    fn part2_rescan(grid: &mut Grid<char>, rules: &Rules) -> usize {
+        let mut res = 0;
+        loop {
+            let accessible: Vec<Pos> = grid
+                .positions()
+                .filter(|&pos| {
+                    grid[pos] == '@' && count_rolls_around(grid, pos, rules) < rules.threshold
+                })
+                .collect();
+            if accessible.is_empty() {
+                return res;
+            }
+            for pos in accessible {
+                grid[pos] = 'x';
+                res += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_removal_rounds_example() {
+        let grid = parse(include_str!("../example.txt"));
+        let rounds = removal_rounds(&grid, &Rules::default());
+        assert_eq!(rounds.iter().filter_map(|(_, r)| *r).max(), Some(9));
+        assert_eq!(
+            rounds.iter().filter(|(_, r)| **r == Some(1)).count(),
+            part1(&Diagram { grid: grid.clone() }, &Rules::default())
+        );
+        assert_eq!(
+            rounds_map(&grid, &rounds).lines().next(),
+            Some("..11.1121.")
+        );
+        assert_eq!(
+            rounds_csv(&grid, &rounds).lines().next(),
+            Some(",,1,1,,1,1,2,1,")
+        );
+    }
+
+    #[test]
+    fn test_part2_matches_rescan() {
+        // Grilles pseudo-aléatoires de densités variées
+        let mut seed = 12345u64;
+        for density in [30, 50, 70, 90] {
+            let rows = (0..40)
+                .map(|_| {
+                    (0..40)
+                        .map(|_| {
+                            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                            if (seed >> 33) % 100 < density {
+                                '@'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            let grid = Grid::from_rows(rows);
+
+            for rules in [
+                Rules::default(),
+                Rules {
+                    threshold: 2,
+                    neighborhood: Neighborhood::VonNeumann,
+                    topology: Topology::Bounded,
+                },
+                Rules {
+                    threshold: 5,
+                    neighborhood: Neighborhood::Moore,
+                    topology: Topology::Torus,
+                },
+                Rules {
+                    threshold: 2,
+                    neighborhood: Neighborhood::Custom(vec![(0, 2), (0, -2), (2, 1)]),
+                    topology: Topology::Torus,
+                },
+            ] {
+                let mut rescanned = grid.clone();
+                let expected = part2_rescan(&mut rescanned, &rules);
+
+                let rounds = removal_rounds(&grid, &rules);
+                assert_eq!(part2(&rounds), expected, "{rules:?}");
+                for (pos, round) in rounds.iter() {
+                    assert_eq!(round.is_some(), rescanned[pos] == 'x', "{rules:?} {pos}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_torus() {
+        // Sur un tore 3x3 plein, chaque rouleau a ses 8 voisins : aucun n'est accessible
+        let diagram = Diagram {
+            grid: parse("@@@\n@@@\n@@@"),
+        };
+        assert_eq!(part1(&diagram, &Rules::default()), 4);
+        let torus = Rules {
+            topology: Topology::Torus,
+            ..Rules::default()
+        };
+        assert_eq!(part1(&diagram, &torus), 0);
+    }
+
+    #[test]
+    fn test_rules_from_args() {
+        assert_eq!(Rules::from_args(&split_args("day04")), Rules::default());
+        assert_eq!(
+            Rules::from_args(&split_args(
+                "day04 --threshold 2 --neighborhood -1:0,1:0 --torus"
+            )),
+            Rules {
+                threshold: 2,
+                neighborhood: Neighborhood::Custom(vec![(-1, 0), (1, 0)]),
+                topology: Topology::Torus,
+            }
+        );
+        assert_eq!(
+            Rules::from_args(&split_args("day04 --neighborhood von-neumann")).offsets(),
+            &DIRS4
+        );
     }
 }
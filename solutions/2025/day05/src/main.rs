@@ -1,84 +1,161 @@
+use common::IntervalSet;
+
 fn main() {
     let input = include_str!("../input.txt");
     let (ranges, ids) = parse(input);
+    let fresh: IntervalSet<u64> = ranges.iter().copied().collect();
 
     let start = std::time::Instant::now();
-    println!("Part 1: {}", part1(&ranges, &ids));
+    println!("Part 1: {}", part1(&fresh, &ids));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
 
     let start = std::time::Instant::now();
-    println!("Part 2: {}", part2(&ranges));
+    println!("Part 2: {}", part2(&fresh));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
-}
-
-fn parse(input: &str) -> (Vec<(usize, usize)>, Vec<usize>) {
-    let (ranges_str, ids_str) = input
-        .split_once("\n\n").unwrap();
-
-    let ranges = ranges_str
-        .lines()
-        .map(|l| {
-            let (start, end) = l.split_once("-").unwrap();
-            let start = start.parse::<usize>().unwrap();
-            let end = end.parse::<usize>().unwrap();
-            (start, end)
-        })
-        .collect();
-    let ids: Vec<usize> = ids_str
-        .lines()
-        .map(|l| l.parse::<usize>().unwrap())
-        .collect();
-
-    (ranges, ids)
-}
 
-fn part1(ranges: &[(usize, usize)], ids: &[usize]) -> usize {
-    let mut res = 0;
+    let index = RangeIndex::new(&ranges);
+    if let Some((depth, at)) = index.max_depth() {
+        println!("Max overlap depth: {} (first reached at {})", depth, at);
+    }
 
-    for &id in ids {
-        for &(start, end) in ranges {
-            if id >= start && id <= end {
-                res += 1;
-                break;
+    // `--stdin` : lit des IDs sur l'entrée standard et affiche les plages qui les contiennent
+    if std::env::args().any(|a| a == "--stdin") {
+        for line in std::io::stdin().lines() {
+            let line = line.expect("Read failed");
+            if line.trim().is_empty() {
+                continue;
             }
+            let Ok(id) = line.trim().parse::<u64>() else {
+                eprintln!("{}: invalid ID", line);
+                continue;
+            };
+            let matches: Vec<String> = index
+                .containing(id)
+                .iter()
+                .map(|&i| format!("#{} {}-{}", i + 1, ranges[i].0, ranges[i].1))
+                .collect();
+            println!(
+                "{}: {} range(s) [{}]",
+                id,
+                matches.len(),
+                matches.join(", ")
+            );
         }
     }
+}
 
-    res
+// Index des plages d'origine (avant fusion) pour savoir lesquelles contiennent un ID.
+// Plages triées par début + arbre de segments du maximum des fins : on ne descend que
+// dans les nœuds dont une plage finit après `x`. Chaque réponse peut coûter son propre
+// chemin racine-feuille, d'où O((réponses + 1)·log n) par requête.
+struct RangeIndex {
+    sorted: Vec<(u64, u64, usize)>, // (début, fin, numéro dans l'entrée)
+    max_end: Vec<u64>,
 }
 
-fn part2(ranges: &[(usize, usize)]) -> usize {
-    let mut res = 0;
+impl RangeIndex {
+    fn new(ranges: &[(u64, u64)]) -> RangeIndex {
+        let mut sorted: Vec<(u64, u64, usize)> = ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, &(start, end))| start <= end)
+            .map(|(i, &(start, end))| (start, end, i))
+            .collect();
+        sorted.sort_unstable();
 
-    let mut ranges = ranges.to_vec();
+        let mut index = RangeIndex {
+            max_end: vec![0; 4 * sorted.len().max(1)],
+            sorted,
+        };
+        if !index.sorted.is_empty() {
+            index.build(1, 0, index.sorted.len());
+        }
+        index
+    }
 
-    ranges.sort_by_key(|&(start, _)| start);
+    fn build(&mut self, node: usize, l: usize, r: usize) -> u64 {
+        self.max_end[node] = if r - l == 1 {
+            self.sorted[l].1
+        } else {
+            let mid = (l + r) / 2;
+            self.build(2 * node, l, mid)
+                .max(self.build(2 * node + 1, mid, r))
+        };
+        self.max_end[node]
+    }
 
-    let mut prev_max = 0;
+    // Numéros (dans l'ordre de l'entrée) des plages contenant `x`
+    fn containing(&self, x: u64) -> Vec<usize> {
+        let mut res = vec![];
+        // Seules les plages qui commencent avant `x` sont candidates
+        let candidates = self.sorted.partition_point(|&(start, _, _)| start <= x);
+        if candidates > 0 {
+            self.collect(1, 0, self.sorted.len(), candidates, x, &mut res);
+        }
+        res.sort_unstable();
+        res
+    }
 
-    for index in 0..ranges.len() - 1 {
-        let (_, end) = ranges[index];
-        prev_max = prev_max.max(end);
+    fn collect(&self, node: usize, l: usize, r: usize, k: usize, x: u64, res: &mut Vec<usize>) {
+        if l >= k || self.max_end[node] < x {
+            return;
+        }
+        if r - l == 1 {
+            res.push(self.sorted[l].2);
+            return;
+        }
+        let mid = (l + r) / 2;
+        self.collect(2 * node, l, mid, k, x, res);
+        self.collect(2 * node + 1, mid, r, k, x, res);
+    }
 
-        let next = &mut ranges[index + 1];
+    // Nombre maximal de plages qui se chevauchent, et le plus petit ID où il est atteint
+    fn max_depth(&self) -> Option<(usize, u64)> {
+        // Balayage : +1 au début d'une plage, -1 juste après sa fin (u128 pour u64::MAX + 1)
+        let mut events: Vec<(u128, i64)> = self
+            .sorted
+            .iter()
+            .flat_map(|&(start, end, _)| [(start as u128, 1), (end as u128 + 1, -1)])
+            .collect();
+        // À position égale, les fins passent avant les débuts
+        events.sort_unstable();
 
-        if prev_max >= next.0 {
-            if prev_max + 1 > next.1 {
-                next.0 = 0;
-                next.1 = 0;
-            } else {
-                next.0 = prev_max + 1;
+        let mut depth = 0i64;
+        let mut best: Option<(usize, u64)> = None;
+        for (at, delta) in events {
+            depth += delta;
+            if best.is_none_or(|(d, _)| depth as usize > d) && delta > 0 {
+                best = Some((depth as usize, at as u64));
             }
         }
+        best
     }
+}
 
-    for (start, end) in ranges {
-        if end != 0 {
-            res += end - start + 1;
-        }
-    }
+fn parse(input: &str) -> (Vec<(u64, u64)>, Vec<u64>) {
+    // Sans ligne vide, l'entrée ne contient que des plages (éventuellement aucune)
+    let (ranges_str, ids_str) = input.split_once("\n\n").unwrap_or((input, ""));
 
-    res
+    let ranges = ranges_str
+        .lines()
+        .map(|l| {
+            let (start, end) = l.split_once("-").unwrap();
+            let start = start.parse::<u64>().unwrap();
+            let end = end.parse::<u64>().unwrap();
+            (start, end)
+        })
+        .collect();
+    let ids: Vec<u64> = ids_str.lines().map(|l| l.parse::<u64>().unwrap()).collect();
+
+    (ranges, ids)
+}
+
+fn part1(fresh: &IntervalSet<u64>, ids: &[u64]) -> usize {
+    ids.iter().filter(|&&id| fresh.contains(id)).count()
+}
+
+fn part2(fresh: &IntervalSet<u64>) -> u128 {
+    fresh.len()
 }
 
 #[cfg(test)]
@@ -89,13 +166,95 @@ mod tests {
     fn test_part1_example() {
         let example_input = include_str!("../example.txt");
         let (ranges, ids) = parse(example_input);
-        assert_eq!(part1(&ranges, &ids), 3);
+        let fresh = IntervalSet::from_ranges(ranges);
+        assert_eq!(part1(&fresh, &ids), 3);
     }
 
     #[test]
     fn test_part2_example() {
         let example_input = include_str!("../example.txt");
         let (ranges, _) = parse(example_input);
-        assert_eq!(part2(&ranges), 14);
+        assert_eq!(part2(&IntervalSet::from_ranges(ranges)), 14);
+    }
+
+    #[test]
+    fn test_ranges_at_zero() {
+        let (ranges, ids) = parse("0-0\n5-7\n\n0\n1\n6");
+        let fresh = IntervalSet::from_ranges(ranges);
+        assert_eq!(part1(&fresh, &ids), 2);
+        assert_eq!(part2(&fresh), 1 + 3);
+
+        // Une plage qui finit à 0 ne doit pas disparaître à la fusion
+        let (ranges, _) = parse("0-0\n0-0\n1-2\n");
+        assert_eq!(part2(&IntervalSet::from_ranges(ranges)), 3);
+    }
+
+    #[test]
+    fn test_ranges_at_u64_max() {
+        let max = u64::MAX;
+        let (ranges, ids) = parse(&format!("{}-{}\n0-{}\n\n{}", max - 1, max, max - 2, max));
+        let fresh = IntervalSet::from_ranges(ranges);
+        assert_eq!(part1(&fresh, &ids), 1);
+        assert_eq!(part2(&fresh), 1 << 64);
+    }
+
+    #[test]
+    fn test_empty_inputs() {
+        let (ranges, ids) = parse("");
+        assert!(ranges.is_empty() && ids.is_empty());
+        assert_eq!(part2(&IntervalSet::from_ranges(ranges)), 0);
+
+        let (ranges, ids) = parse("3-5\n");
+        assert_eq!(ranges, vec![(3, 5)]);
+        assert!(ids.is_empty());
+
+        let (ranges, ids) = parse("\n\n4\n");
+        let fresh = IntervalSet::from_ranges(ranges);
+        assert_eq!(part1(&fresh, &ids), 0);
+        assert_eq!(part2(&fresh), 0);
+    }
+
+    #[test]
+    fn test_range_index_containing() {
+        let (ranges, _) = parse(include_str!("../example.txt"));
+        let index = RangeIndex::new(&ranges);
+        assert_eq!(index.containing(1), vec![]);
+        assert_eq!(index.containing(5), vec![0]);
+        assert_eq!(index.containing(17), vec![2, 3]);
+        assert_eq!(index.containing(32), vec![]);
+
+        // Comparaison avec un parcours de toutes les plages
+        let ranges = vec![
+            (0, 0),
+            (2, 9),
+            (4, 4),
+            (3, 30),
+            (8, 12),
+            (25, 26),
+            (7, 1),
+            (6, 12),
+        ];
+        let index = RangeIndex::new(&ranges);
+        for x in 0..35 {
+            let expected: Vec<usize> = (0..ranges.len())
+                .filter(|&i| ranges[i].0 <= x && x <= ranges[i].1)
+                .collect();
+            assert_eq!(index.containing(x), expected, "{x}");
+        }
+    }
+
+    #[test]
+    fn test_range_index_max_depth() {
+        let (ranges, _) = parse(include_str!("../example.txt"));
+        assert_eq!(RangeIndex::new(&ranges).max_depth(), Some((2, 12)));
+
+        // Plages contiguës : pas de chevauchement
+        assert_eq!(RangeIndex::new(&[(1, 3), (4, 6)]).max_depth(), Some((1, 1)));
+        assert_eq!(
+            RangeIndex::new(&[(5, u64::MAX), (u64::MAX, u64::MAX)]).max_depth(),
+            Some((2, u64::MAX))
+        );
+        assert_eq!(RangeIndex::new(&[]).max_depth(), None);
+        assert_eq!(RangeIndex::new(&[]).containing(3), vec![]);
     }
 }
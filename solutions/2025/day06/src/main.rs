@@ -1,120 +1,440 @@
+use common::Grid;
+use std::fmt;
+
 fn main() {
     let input = include_str!("../input.txt");
-    let problems = parse_p1(input);
+    let worksheet = match Worksheet::parse(input) {
+        Ok(worksheet) => worksheet,
+        Err(e) => {
+            eprintln!("Invalid worksheet: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     let start = std::time::Instant::now();
-    println!("Part 1: {}", part1(&problems));
+    match part1(&worksheet) {
+        Ok(res) => println!("Part 1: {}", res),
+        Err(e) => eprintln!("Part 1: {}", e),
+    }
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
 
     let start = std::time::Instant::now();
-    let operations = parse_p2(input);
-    println!("Part 2: {}", part2(&operations));
+    match part2(&worksheet) {
+        Ok(res) => println!("Part 2: {}", res),
+        Err(e) => eprintln!("Part 2: {}", e),
+    }
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    // `--annotate` : la feuille avec le résultat de chaque problème sous son bloc
+    if std::env::args().any(|a| a == "--annotate") {
+        print!("{}", annotate(&worksheet));
+    }
 }
 
-fn parse_p1(input: &str) -> Vec<Vec<&str>> {
-    input
-        .lines()
-        .map(|s| s.split_whitespace().collect::<Vec<&str>>())
-        .collect::<Vec<Vec<&str>>>()
+// Lecture des opérandes d'un problème
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reading {
+    // Un nombre par ligne (part1)
+    Rows,
+    // Un nombre par colonne, chiffres de haut en bas, colonnes de droite à gauche (part2)
+    Columns,
 }
 
-fn parse_p2(input: &str) -> Vec<Vec<char>> {
-    let problems = input
-        .lines()
-        .map(|s| s.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
+// Les opérandes sont combinés de gauche à droite dans l'ordre de lecture :
+// `-` sur 10, 3, 2 donne (10 - 3) - 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,    // +
+    Sub,    // -
+    Mul,    // *
+    Div,    // / (division entière)
+    Pow,    // ^
+    Min,    // <
+    Max,    // >
+    Concat, // | (12 | 34 = 1234)
+}
 
-    let r = problems.len();
-    let c = problems[0].len();
-    let mut operation_lines = vec![vec![' '; r]; c];
+impl Operator {
+    fn from_char(c: char) -> Option<Operator> {
+        match c {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Sub),
+            '*' => Some(Operator::Mul),
+            '/' => Some(Operator::Div),
+            '^' => Some(Operator::Pow),
+            '<' => Some(Operator::Min),
+            '>' => Some(Operator::Max),
+            '|' => Some(Operator::Concat),
+            _ => None,
+        }
+    }
 
-    for i in 0..r {
-        for j in 0..c {
-            operation_lines[j][i] = problems[i][j];
+    fn apply(self, a: u64, b: u64) -> Result<u64, EvalErrorKind> {
+        match self {
+            Operator::Add => a.checked_add(b).ok_or(EvalErrorKind::Overflow),
+            Operator::Sub => a.checked_sub(b).ok_or(EvalErrorKind::Negative),
+            Operator::Mul => a.checked_mul(b).ok_or(EvalErrorKind::Overflow),
+            Operator::Div => a.checked_div(b).ok_or(EvalErrorKind::DivisionByZero),
+            // 0 et 1 ont une puissance définie même quand l'exposant ne tient pas sur u32
+            Operator::Pow if a <= 1 => Ok(if b == 0 { 1 } else { a }),
+            Operator::Pow => u32::try_from(b)
+                .ok()
+                .and_then(|b| a.checked_pow(b))
+                .ok_or(EvalErrorKind::Overflow),
+            Operator::Min => Ok(a.min(b)),
+            Operator::Max => Ok(a.max(b)),
+            Operator::Concat => {
+                let digits = b.checked_ilog10().unwrap_or(0) + 1;
+                10u64
+                    .checked_pow(digits)
+                    .and_then(|shift| a.checked_mul(shift))
+                    .and_then(|a| a.checked_add(b))
+                    .ok_or(EvalErrorKind::Overflow)
+            }
         }
     }
+}
 
-    operation_lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EvalErrorKind {
+    Overflow,
+    Negative,
+    DivisionByZero,
+    // Lecture par lignes : deux nombres séparés par un espace sur la même ligne
+    SplitNumber,
+    // Lecture par colonnes : une colonne du problème sans aucun chiffre
+    ColumnWithoutDigit,
 }
 
-fn part1(input: &[Vec<&str>]) -> usize {
-    let mut res = 0;
-    let r = input.len();
-    let c = input[0].len();
-    let mut ops = vec![vec![""; r]; c];
+// Erreur d'un problème dans une lecture donnée (opérandes ou évaluation), avec la colonne
+// (à partir de 1) où commence le problème
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EvalError {
+    kind: EvalErrorKind,
+    column: usize,
+}
 
-    for i in 0..r {
-        for j in 0..c {
-            ops[j][i] = input[i][j];
-        }
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            EvalErrorKind::Overflow => "overflow",
+            EvalErrorKind::Negative => "negative result",
+            EvalErrorKind::DivisionByZero => "division by zero",
+            EvalErrorKind::SplitNumber => "two numbers on one line",
+            EvalErrorKind::ColumnWithoutDigit => "column without digit",
+        };
+        write!(f, "{} in the problem at column {}", kind, self.column)
     }
+}
 
-    for op in &ops {
-        let operator = op[op.len() - 1];
-        let operands = &op[..op.len() - 1]
-            .iter()
-            .map(|x| x.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>();
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Problem {
+    operator: Operator,
+    operands: Vec<u64>,
+    column: usize,
+}
 
-        let val = match operator {
-            "+" => operands.iter().fold(0, |acc, &x| acc + x),
-            "*" => operands.iter().fold(1, |acc, &x| acc * x),
-            &_ => unreachable!("Unexpected operator in part1"),
+impl Problem {
+    fn eval(&self) -> Result<u64, EvalError> {
+        let error = |kind| EvalError {
+            kind,
+            column: self.column,
         };
+        let (&first, rest) = self
+            .operands
+            .split_first()
+            .expect("Problem without operand");
 
-        res += val;
+        rest.iter()
+            .try_fold(first, |acc, &x| self.operator.apply(acc, x).map_err(error))
     }
+}
 
-    res
+// Erreurs de structure de la feuille (lignes et colonnes comptées à partir de 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WorksheetError {
+    Empty,
+    MissingOperatorRow,
+    UnknownOperator {
+        column: usize,
+        found: char,
+    },
+    OperatorNotAtBlockStart {
+        column: usize,
+    },
+    MissingOperator {
+        column: usize,
+    },
+    InvalidCharacter {
+        row: usize,
+        column: usize,
+        found: char,
+    },
+    MissingOperand {
+        column: usize,
+    },
 }
 
-fn calc(operator: char, values: &[u32]) -> u64 {
-    let val: u64 = match operator {
-        '+' => values.iter().fold(0, |acc, &x| acc + x as u64),
-        '*' => values.iter().fold(1, |acc, &x| acc * x as u64),
-        _ => unreachable!("Unexpected operator in part2"),
-    };
+impl fmt::Display for WorksheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorksheetError::Empty => write!(f, "empty worksheet"),
+            WorksheetError::MissingOperatorRow => {
+                write!(f, "the last line holds no operator")
+            }
+            WorksheetError::UnknownOperator { column, found } => {
+                write!(f, "unknown operator '{}' at column {}", found, column)
+            }
+            WorksheetError::OperatorNotAtBlockStart { column } => write!(
+                f,
+                "operator at column {} is not at the start of its problem",
+                column
+            ),
+            WorksheetError::MissingOperator { column } => {
+                write!(
+                    f,
+                    "no operator for the problem starting at column {}",
+                    column
+                )
+            }
+            WorksheetError::InvalidCharacter { row, column, found } => write!(
+                f,
+                "unexpected '{}' at line {}, column {}",
+                found, row, column
+            ),
+            WorksheetError::MissingOperand { column } => write!(
+                f,
+                "the problem starting at column {} has no operand",
+                column
+            ),
+        }
+    }
+}
 
-    val
+// Feuille de calcul : la grille de caractères et les blocs de colonnes de chaque problème,
+// séparés par des colonnes entièrement vides. La dernière ligne porte les opérateurs,
+// chacun dans la première colonne de son problème. Les lignes plus courtes que la plus
+// longue (espaces de fin supprimés par un éditeur) sont complétées par des espaces.
+struct Worksheet {
+    grid: Grid<char>,
+    blocks: Vec<(usize, usize)>, // colonnes [début, fin) de chaque problème
 }
 
-fn part2(operations_lines: &[Vec<char>]) -> u64 {
-    let mut operator = '#';
-    let mut op_res = 0;
-    let mut operator_line = true;
-    let mut values_line: Vec<u32> = vec![];
+impl Worksheet {
+    fn parse(input: &str) -> Result<Worksheet, WorksheetError> {
+        let lines: Vec<&str> = input.lines().filter(|l| !l.trim().is_empty()).collect();
+        if lines.is_empty() {
+            return Err(WorksheetError::Empty);
+        }
+        let grid = Grid::from_rows_padded(lines.iter().map(|l| l.chars().collect()).collect(), ' ');
 
-    for op_line in operations_lines {
-        let mut val = 0;
+        let operator_row = grid.row(grid.height() - 1);
+        if !operator_row
+            .iter()
+            .any(|&c| Operator::from_char(c).is_some())
+        {
+            return Err(WorksheetError::MissingOperatorRow);
+        }
+        for (col, &c) in operator_row.iter().enumerate() {
+            if c != ' ' && Operator::from_char(c).is_none() {
+                return Err(WorksheetError::UnknownOperator {
+                    column: col + 1,
+                    found: c,
+                });
+            }
+        }
+        for row in 0..grid.height() - 1 {
+            for (col, &c) in grid.row(row).iter().enumerate() {
+                if c != ' ' && !c.is_ascii_digit() {
+                    return Err(WorksheetError::InvalidCharacter {
+                        row: row + 1,
+                        column: col + 1,
+                        found: c,
+                    });
+                }
+            }
+        }
 
-        if op_line.iter().all(|&c| c == ' ') {
-            op_res += calc(operator, &values_line);
-            operator_line = true;
-            values_line.clear();
-            continue;
+        let mut blocks = vec![];
+        let mut block_start = None;
+        for col in 0..=grid.width() {
+            let blank = col == grid.width() || grid.column(col).all(|&c| c == ' ');
+            match (block_start, blank) {
+                (None, false) => block_start = Some(col),
+                (Some(start), true) => {
+                    blocks.push((start, col));
+                    block_start = None;
+                }
+                _ => {}
+            }
         }
 
-        // L'operateur est toujours le dernier char de la ligne
-        if operator_line {
-            operator = *op_line.last().unwrap();
-            operator_line = false;
+        // Un seul opérateur par problème, dans sa première colonne
+        for &(start, end) in &blocks {
+            let mut operators = (start..end).filter(|&col| operator_row[col] != ' ');
+            match operators.next() {
+                None => return Err(WorksheetError::MissingOperator { column: start + 1 }),
+                Some(col) if col != start => {
+                    return Err(WorksheetError::OperatorNotAtBlockStart { column: col + 1 })
+                }
+                Some(_) => {}
+            }
+            if let Some(col) = operators.next() {
+                return Err(WorksheetError::OperatorNotAtBlockStart { column: col + 1 });
+            }
         }
 
-        for &c in op_line {
-            // On lit les digits du haut vers le bas (du plus significatif au moins significatif) :
-            // val = val*10 + d fonctionne car au 1er digit val = 0, donc le *10 est indolore.
-            if let Some(d) = c.to_digit(10) {
-                val = val * 10 + d;
+        // Un problème sans aucun chiffre n'a d'opérande dans aucune lecture ; les contraintes
+        // propres à une lecture sont vérifiées par `problem`
+        let digit_rows = grid.height() - 1;
+        for &(start, end) in &blocks {
+            if (start..end).all(|col| grid.column(col).take(digit_rows).all(|&c| c == ' ')) {
+                return Err(WorksheetError::MissingOperand { column: start + 1 });
             }
         }
-        values_line.push(val);
+
+        Ok(Worksheet { grid, blocks })
+    }
+
+    // Un problème dont les opérandes ne se lisent pas dans cette lecture est une erreur
+    fn problems(&self, reading: Reading) -> Vec<Result<Problem, EvalError>> {
+        self.blocks
+            .iter()
+            .map(|&(start, end)| self.problem(start, end, reading))
+            .collect()
     }
 
-    // Add last line result
-    op_res += calc(operator, &values_line);
+    fn problem(&self, start: usize, end: usize, reading: Reading) -> Result<Problem, EvalError> {
+        let error = |kind| EvalError {
+            kind,
+            column: start + 1,
+        };
+        let overflow = error(EvalErrorKind::Overflow);
+        let operator_row = self.grid.height() - 1;
+        let operator = Operator::from_char(self.grid.row(operator_row)[start]).unwrap();
+
+        let digit_rows = (0..operator_row).map(|row| &self.grid.row(row)[start..end]);
 
-    op_res
+        let operands = match reading {
+            // Une ligne vide dans un bloc (ligne courte complétée) ne porte pas d'opérande
+            Reading::Rows => digit_rows
+                .map(|cells| cells.iter().collect::<String>())
+                .filter(|number| !number.trim().is_empty())
+                .map(|number| match number.trim() {
+                    n if n.contains(' ') => Err(error(EvalErrorKind::SplitNumber)),
+                    n => n.parse::<u64>().map_err(|_| overflow),
+                })
+                .collect::<Result<_, _>>()?,
+            Reading::Columns => {
+                let rows: Vec<&[char]> = digit_rows.collect();
+                (0..end - start)
+                    .rev()
+                    .map(|col| {
+                        if rows.iter().all(|row| row[col] == ' ') {
+                            return Err(error(EvalErrorKind::ColumnWithoutDigit));
+                        }
+                        // On lit les digits du haut vers le bas (du plus significatif au moins
+                        // significatif) : val = val*10 + d fonctionne car au 1er digit val = 0.
+                        rows.iter()
+                            .filter_map(|row| row[col].to_digit(10))
+                            .try_fold(0u64, |val, d| val.checked_mul(10)?.checked_add(d as u64))
+                            .ok_or(overflow)
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        Ok(Problem {
+            operator,
+            operands,
+            column: start + 1,
+        })
+    }
+}
+
+fn total(worksheet: &Worksheet, reading: Reading) -> Result<u64, EvalError> {
+    worksheet
+        .problems(reading)
+        .iter()
+        .try_fold(0u64, |acc, problem| {
+            let problem = problem.as_ref().map_err(|e| *e)?;
+            acc.checked_add(problem.eval()?).ok_or(EvalError {
+                kind: EvalErrorKind::Overflow,
+                column: problem.column,
+            })
+        })
+}
+
+// Deux copies de la feuille côte à côte (lecture par lignes à gauche, par colonnes à
+// droite), avec sous chaque bloc un trait puis le résultat du problème aligné à droite.
+// Les blocs sont élargis si un résultat dépasse, et séparés par une seule colonne vide.
+fn annotate(worksheet: &Worksheet) -> String {
+    let results = |reading| -> Vec<String> {
+        worksheet
+            .problems(reading)
+            .iter()
+            .map(
+                |problem| match problem.as_ref().map_err(|e| *e).and_then(Problem::eval) {
+                    Ok(value) => value.to_string(),
+                    Err(_) => "error".to_string(),
+                },
+            )
+            .collect()
+    };
+    let (rows, columns) = (results(Reading::Rows), results(Reading::Columns));
+
+    let widths: Vec<usize> = worksheet
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| (end - start).max(rows[i].len()).max(columns[i].len()))
+        .collect();
+    let join = |cells: Vec<String>| cells.join(" ");
+
+    let mut lines: Vec<String> = (0..worksheet.grid.height())
+        .map(|row| {
+            join(
+                worksheet
+                    .blocks
+                    .iter()
+                    .zip(&widths)
+                    .map(|(&(start, end), &width)| {
+                        let cells: String = worksheet.grid.row(row)[start..end].iter().collect();
+                        format!("{:<width$}", cells)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    lines.push(join(
+        widths.iter().map(|&width| "-".repeat(width)).collect(),
+    ));
+    let result_line = |results: &[String]| {
+        join(
+            results
+                .iter()
+                .zip(&widths)
+                .map(|(result, &width)| format!("{:>width$}", result))
+                .collect(),
+        )
+    };
+
+    let panel_width = lines[0].chars().count();
+    let mut out = format!("{:<panel_width$}   {}\n", "Rows", "Columns");
+    for line in &lines {
+        out += format!("{}   {}", line, line).trim_end();
+        out.push('\n');
+    }
+    out += &format!("{}   {}\n", result_line(&rows), result_line(&columns));
+    out
+}
+
+fn part1(worksheet: &Worksheet) -> Result<u64, EvalError> {
+    total(worksheet, Reading::Rows)
+}
+
+fn part2(worksheet: &Worksheet) -> Result<u64, EvalError> {
+    total(worksheet, Reading::Columns)
 }
 
 #[cfg(test)]
@@ -124,12 +444,225 @@ mod tests {
     #[test]
     fn test_part1_example() {
         let example_input = include_str!("../example.txt");
-        assert_eq!(part1(&parse_p1(example_input)), 4277556);
+        assert_eq!(
+            part1(&Worksheet::parse(example_input).unwrap()).unwrap(),
+            4277556
+        );
     }
 
     #[test]
     fn test_part2_example() {
         let example_input = include_str!("../example.txt");
-        assert_eq!(part2(&parse_p2(example_input)), 3263827);
+        assert_eq!(
+            part2(&Worksheet::parse(example_input).unwrap()).unwrap(),
+            3263827
+        );
+    }
+
+    #[test]
+    fn test_problems_both_readings() {
+        let worksheet = Worksheet::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(worksheet.blocks, vec![(0, 3), (4, 7), (8, 11), (12, 15)]);
+
+        let first = Problem {
+            operator: Operator::Mul,
+            operands: vec![123, 45, 6],
+            column: 1,
+        };
+        assert_eq!(worksheet.problems(Reading::Rows)[0], Ok(first));
+
+        let last = Problem {
+            operator: Operator::Add,
+            operands: vec![4, 431, 623],
+            column: 13,
+        };
+        assert_eq!(worksheet.problems(Reading::Columns)[3], Ok(last));
+    }
+
+    #[test]
+    fn test_ragged_lines() {
+        // Espaces de fin supprimés sur chaque ligne
+        let example_input = include_str!("../example.txt");
+        let trimmed: String = example_input
+            .lines()
+            .map(|l| format!("{}\n", l.trim_end()))
+            .collect();
+        let worksheet = Worksheet::parse(&trimmed).unwrap();
+        assert_eq!(part1(&worksheet), Ok(4277556));
+        assert_eq!(part2(&worksheet), Ok(3263827));
+
+        // Ligne plus longue que la première : rien n'est tronqué
+        let worksheet = Worksheet::parse("1 2\n3 45\n+ *").unwrap();
+        assert_eq!(part1(&worksheet), Ok(4 + 90));
+        assert_eq!(part2(&worksheet), Ok(13 + 5 * 24));
+    }
+
+    #[test]
+    fn test_structural_errors() {
+        assert_eq!(Worksheet::parse("").err(), Some(WorksheetError::Empty));
+        assert_eq!(
+            Worksheet::parse("12 3\n4  5").err(),
+            Some(WorksheetError::MissingOperatorRow)
+        );
+        assert_eq!(
+            Worksheet::parse("12 3\n4  5\n+  %").err(),
+            Some(WorksheetError::UnknownOperator {
+                column: 4,
+                found: '%'
+            })
+        );
+        assert_eq!(
+            Worksheet::parse("12 3\n45 6\n *  +").err(),
+            Some(WorksheetError::OperatorNotAtBlockStart { column: 2 })
+        );
+        assert_eq!(
+            Worksheet::parse("12 3\n45 6\n+").err(),
+            Some(WorksheetError::MissingOperator { column: 4 })
+        );
+        assert_eq!(
+            Worksheet::parse("12 3\n4x 6\n+  +").err(),
+            Some(WorksheetError::InvalidCharacter {
+                row: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn test_extended_operators() {
+        let eval = |operator, operands: &[u64]| {
+            Problem {
+                operator,
+                operands: operands.to_vec(),
+                column: 1,
+            }
+            .eval()
+        };
+        assert_eq!(eval(Operator::Sub, &[10, 3, 2]), Ok(5));
+        assert_eq!(eval(Operator::Div, &[100, 7, 2]), Ok(7));
+        assert_eq!(eval(Operator::Pow, &[2, 3, 2]), Ok(64));
+        assert_eq!(eval(Operator::Min, &[5, 2, 9]), Ok(2));
+        assert_eq!(eval(Operator::Max, &[5, 2, 9]), Ok(9));
+        assert_eq!(eval(Operator::Concat, &[12, 0, 345]), Ok(120345));
+
+        let worksheet = Worksheet::parse("10 2  8 7\n 3 3  2 1\n-  ^  | >").unwrap();
+        assert_eq!(part1(&worksheet), Ok(7 + 8 + 82 + 7));
+    }
+
+    #[test]
+    fn test_checked_evaluation() {
+        let worksheet = Worksheet::parse("1 4  3\n2 0 99\n- / +").unwrap();
+        assert_eq!(
+            part1(&worksheet),
+            Err(EvalError {
+                kind: EvalErrorKind::Negative,
+                column: 1
+            })
+        );
+
+        let worksheet = Worksheet::parse("4  3\n0 99\n/ +").unwrap();
+        let err = part1(&worksheet).unwrap_err();
+        assert_eq!(err.kind, EvalErrorKind::DivisionByZero);
+        assert_eq!(
+            err.to_string(),
+            "division by zero in the problem at column 1"
+        );
+
+        let worksheet = Worksheet::parse("1 99999\n2 99999\n+ *").unwrap();
+        assert_eq!(part1(&worksheet), Ok(3 + 99999 * 99999));
+        let worksheet = Worksheet::parse("1 99999999999\n2 99999999999\n+ ^").unwrap();
+        assert_eq!(
+            part1(&worksheet),
+            Err(EvalError {
+                kind: EvalErrorKind::Overflow,
+                column: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let worksheet = Worksheet::parse(include_str!("../example.txt")).unwrap();
+        let expected = [
+            "Rows                     Columns",
+            "123   328  51     64     123   328  51     64",
+            " 45   64  387     23      45   64  387     23",
+            "  6   98  215     314      6   98  215     314",
+            "*     +   *       +      *     +   *       +",
+            "----- --- ------- ----   ----- --- ------- ----",
+            "33210 490 4243455  401    8544 625 3253600 1058",
+        ];
+        assert_eq!(annotate(&worksheet).lines().collect::<Vec<_>>(), expected);
+
+        let worksheet = Worksheet::parse("4 7\n0 2\n/ -").unwrap();
+        // 4 / 0 en lignes ; en colonnes chaque bloc n'a qu'un opérande (40 et 72)
+        assert!(annotate(&worksheet).ends_with("error  5      40 72\n"));
+    }
+
+    #[test]
+    fn test_operand_errors() {
+        // Opérateur seul dans son bloc : aucune lecture n'a d'opérande
+        assert_eq!(
+            Worksheet::parse("1  \n+ *\n").err(),
+            Some(WorksheetError::MissingOperand { column: 3 })
+        );
+
+        // Deux nombres sur une ligne : invalide par lignes, valide par colonnes (25 + 4 + 13)
+        let worksheet = Worksheet::parse("1 2\n345\n+  ").unwrap();
+        assert_eq!(
+            part1(&worksheet),
+            Err(EvalError {
+                kind: EvalErrorKind::SplitNumber,
+                column: 1
+            })
+        );
+        assert_eq!(part2(&worksheet), Ok(25 + 4 + 13));
+
+        // Colonne d'opérateur sans chiffre : valide par lignes, et pas d'opérande 0 inventé
+        // en lecture par colonnes
+        let worksheet = Worksheet::parse(" 12\n 34\n*  \n").unwrap();
+        assert_eq!(part1(&worksheet), Ok(12 * 34));
+        let err = part2(&worksheet).unwrap_err();
+        assert_eq!(err.kind, EvalErrorKind::ColumnWithoutDigit);
+        assert_eq!(
+            err.to_string(),
+            "column without digit in the problem at column 1"
+        );
+    }
+
+    #[test]
+    fn test_operand_overflow() {
+        // Opérande de 21 chiffres : en ligne comme en colonne
+        let digits = "123456789012345678901";
+        let worksheet = Worksheet::parse(&format!("7 {}\n+ *", digits)).unwrap();
+        let overflow = Err(EvalError {
+            kind: EvalErrorKind::Overflow,
+            column: 3,
+        });
+        assert_eq!(part1(&worksheet), overflow);
+
+        let column: String = digits.chars().map(|d| format!("  {}\n", d)).collect();
+        let worksheet = Worksheet::parse(&format!("4{}+ *", &column[1..])).unwrap();
+        assert_eq!(part2(&worksheet), overflow);
+    }
+
+    #[test]
+    fn test_pow_large_exponent() {
+        let eval = |operands: &[u64]| {
+            Problem {
+                operator: Operator::Pow,
+                operands: operands.to_vec(),
+                column: 1,
+            }
+            .eval()
+        };
+        assert_eq!(eval(&[1, 1 << 40]), Ok(1));
+        assert_eq!(eval(&[0, 1 << 40]), Ok(0));
+        assert_eq!(eval(&[0, 0]), Ok(1));
+        assert_eq!(
+            eval(&[2, 1 << 40]).unwrap_err().kind,
+            EvalErrorKind::Overflow
+        );
     }
 }
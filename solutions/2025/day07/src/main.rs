@@ -1,120 +1,533 @@
+use common::{flag_value, Grid, Pos, RaggedRow};
+use std::collections::BTreeMap;
+use std::fmt;
+
 fn main() {
     let input = include_str!("../input.txt");
-    let diagram = parse(input);
+    let args: Vec<String> = std::env::args().collect();
+    let table = match CellTable::from_args(&args) {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Invalid --cell: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let manifold = match parse(input, &table) {
+        Ok(manifold) => manifold,
+        Err(e) => {
+            eprintln!("Invalid manifold: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // `--clamp` : une branche qui sortirait par un côté reste dans la colonne du bord
+    let edge = if args.iter().any(|a| a == "--clamp") {
+        Edge::Clamped
+    } else {
+        Edge::Lost
+    };
+    let sweep = sweep(&manifold, edge);
 
     let start = std::time::Instant::now();
-    println!("Part 1: {}", part1(&diagram));
+    println!("Part 1: {}", part1(&sweep));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
 
     let start = std::time::Instant::now();
-    println!("Part 2: {}", part2(&diagram));
+    println!("Part 2: {}", part2(&sweep));
     println!("Time: {:.4}ms", start.elapsed().as_secs_f64() * 1000.0);
-}
 
-fn parse(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|line| line.chars().collect()).collect()
+    println!("Distinct beams out: {}", sweep.exits(Semantics::Beams));
+
+    // `--stats <fichier>` : passages par splitter et sorties (CSV si l'extension est .csv,
+    // carte de chaleur ASCII sinon)
+    if let Some(path) = flag_value(&args, "--stats") {
+        let content = if path.ends_with(".csv") {
+            stats_csv(&manifold, &sweep)
+        } else {
+            heatmap(&sweep)
+        };
+        std::fs::write(path, content).expect("Write failed");
+    }
 }
 
-fn part1(diagram: &[Vec<char>]) -> usize {
-    let mut res = 0;
-    let width = diagram[0].len();
-    let start_index = diagram[0].iter().position(|&s| s == 'S').unwrap();
-    let mut beams = vec![false; width];
-    beams[start_index] = true;
+// Effet d'une case sur un faisceau qui y arrive par le haut
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cell {
+    Pass,
+    // Injecte une timeline dans sa colonne, puis laisse passer comme `Pass`
+    Source,
+    Absorb,
+    // Le faisceau repart sur la ligne suivante décalé de chaque offset de colonne :
+    // [-1, 1] pour `^`, un seul offset pour un déflecteur
+    Branch(Vec<isize>),
+}
 
-    for line in diagram[1..].iter() {
-        if line.iter().all(|&c| c == '.') {
-            continue;
+impl Cell {
+    // `pass`, `source`, `absorb` ou une liste d'offsets séparés par des virgules
+    fn parse(s: &str) -> Option<Cell> {
+        match s {
+            "pass" => Some(Cell::Pass),
+            "source" => Some(Cell::Source),
+            "absorb" => Some(Cell::Absorb),
+            _ => s
+                .split(',')
+                .map(|o| o.trim().parse().ok())
+                .collect::<Option<Vec<isize>>>()
+                .map(Cell::Branch),
         }
+    }
+}
 
-        for (i, &c) in line.iter().enumerate() {
-            if c == '^' && beams[i] {
-                if i > 0 {
-                    beams[i - 1] = true;
-                }
+// Table des types de cases : caractère -> effet
+#[derive(Debug, Clone)]
+struct CellTable(Vec<(char, Cell)>);
 
-                if i + 1 < width {
-                    beams[i + 1] = true;
-                }
+impl Default for CellTable {
+    fn default() -> Self {
+        CellTable(vec![
+            ('.', Cell::Pass),
+            ('S', Cell::Source),
+            ('^', Cell::Branch(vec![-1, 1])),
+            ('#', Cell::Absorb),
+            ('<', Cell::Branch(vec![-1])),
+            ('>', Cell::Branch(vec![1])),
+            ('Y', Cell::Branch(vec![-1, 0, 1])),
+        ])
+    }
+}
+
+impl CellTable {
+    fn get(&self, c: char) -> Option<&Cell> {
+        self.0.iter().find(|(k, _)| *k == c).map(|(_, cell)| cell)
+    }
+
+    // Ajoute ou remplace l'effet d'un caractère
+    fn set(&mut self, c: char, cell: Cell) {
+        match self.0.iter_mut().find(|(k, _)| *k == c) {
+            Some(entry) => entry.1 = cell,
+            None => self.0.push((c, cell)),
+        }
+    }
 
-                res += 1;
-                beams[i] = false;
+    // `--cell C=EFFET`, répétable : `--cell 'X=-2,2' --cell 'o=absorb'`
+    fn from_args(args: &[String]) -> Result<CellTable, String> {
+        let mut table = CellTable::default();
+        for (i, arg) in args.iter().enumerate() {
+            if arg != "--cell" {
+                continue;
             }
+            let spec = args.get(i + 1).ok_or("missing value")?;
+            let mut chars = spec.chars();
+            let (Some(c), Some('=')) = (chars.next(), chars.next()) else {
+                return Err(format!("expected C=EFFECT, got '{}'", spec));
+            };
+            let cell = Cell::parse(chars.as_str())
+                .ok_or_else(|| format!("unknown effect in '{}'", spec))?;
+            table.set(c, cell);
         }
+        Ok(table)
     }
+}
 
-    res
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ManifoldError {
+    Ragged(RaggedRow),
+    UnknownCell { pos: Pos, found: char },
 }
 
-fn dp(
-    diagram: &[Vec<char>],
-    line_index: usize,
-    beam_index: usize,
-    cache: &mut Vec<Vec<Option<usize>>>,
-) -> usize {
-    let rows = diagram.len();
-    let cols = diagram[0].len();
+impl fmt::Display for ManifoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifoldError::Ragged(e) => write!(
+                f,
+                "line {} has {} cells, expected {}",
+                e.row + 1,
+                e.len,
+                e.expected
+            ),
+            ManifoldError::UnknownCell { pos, found } => write!(
+                f,
+                "unknown cell '{}' at line {}, column {}",
+                found,
+                pos.row + 1,
+                pos.col + 1
+            ),
+        }
+    }
+}
 
-    if let Some(cached_res) = cache[line_index][beam_index] {
-        return cached_res;
+fn parse(input: &str, table: &CellTable) -> Result<Grid<Cell>, ManifoldError> {
+    let diagram = Grid::try_parse(input).map_err(ManifoldError::Ragged)?;
+    for (pos, &c) in diagram.iter() {
+        if table.get(c).is_none() {
+            return Err(ManifoldError::UnknownCell { pos, found: c });
+        }
     }
+    Ok(diagram.map(|&c| table.get(c).unwrap().clone()))
+}
+
+// Fin d'une timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum End {
+    Bottom(usize), // sortie par le bas, dans cette colonne
+    Side(Pos),     // branche envoyée hors de la grille depuis cette case
+    Absorbed(Pos),
+}
+
+// Sort d'une branche envoyée au-delà du bord gauche ou droit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Lost,    // le faisceau quitte la grille : sa timeline se termine (`End::Side`)
+    Clamped, // le faisceau reste dans la colonne du bord
+}
+
+// Deux façons de compter les faisceaux qui sortent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Semantics {
+    Beams,     // les faisceaux qui se rejoignent fusionnent : un par point de sortie
+    Timelines, // chaque chemin depuis une source compte
+}
 
-    let line = &diagram[line_index];
-    let mut res = 0;
+// Résultat d'un balayage de la grille ligne par ligne
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Sweep {
+    splits: usize,             // cases à plusieurs branches atteintes par un faisceau
+    ends: BTreeMap<End, u128>, // nombre de timelines par point de sortie
+    through: Grid<u128>,       // nombre de timelines qui passent par chaque case
+}
 
-    // Pas de splitter sur la dernière ligne.
-    if line_index == rows - 2 {
-        if line[beam_index] == '^' {
-            res = 1;
+impl Sweep {
+    fn exits(&self, semantics: Semantics) -> u128 {
+        match semantics {
+            Semantics::Beams => self.ends.len() as u128,
+            Semantics::Timelines => self.ends.values().sum(),
         }
-    } else {
-        if line[beam_index] == '^' {
-            res += 1;
+    }
+}
+
+// Balayage itératif du haut vers le bas : `counts[col]` est le nombre de timelines
+// qui arrivent sur la colonne `col` de la ligne courante. Une colonne est éclairée
+// dès que son compteur est non nul, ce qui donne les deux parties en une seule passe.
+// Les branches issues de la dernière ligne sortent par le bas dans leur nouvelle colonne.
+fn sweep(manifold: &Grid<Cell>, edge: Edge) -> Sweep {
+    let width = manifold.width();
+    let mut counts = vec![0u128; width];
 
-            if beam_index > 0 {
-                res += dp(diagram, line_index + 1, beam_index - 1, cache);
+    let mut splits = 0;
+    let mut ends = BTreeMap::new();
+    let mut through = Grid::new(width, manifold.height(), 0u128);
+    for (row, line) in manifold.rows().enumerate() {
+        let mut next = vec![0u128; width];
+        for (col, cell) in line.iter().enumerate() {
+            if *cell == Cell::Source {
+                counts[col] += 1;
+            }
+            let n = counts[col];
+            if n == 0 {
+                continue;
             }
 
-            if beam_index + 1 < cols {
-                res += dp(diagram, line_index + 1, beam_index + 1, cache);
+            let pos = Pos::new(row, col);
+            through[pos] = n;
+            match cell {
+                Cell::Pass | Cell::Source => next[col] += n,
+                Cell::Absorb => *ends.entry(End::Absorbed(pos)).or_insert(0) += n,
+                Cell::Branch(offsets) => {
+                    if offsets.len() > 1 {
+                        splits += 1;
+                    }
+                    for &offset in offsets {
+                        let target = col as isize + offset;
+                        match edge {
+                            _ if (0..width as isize).contains(&target) => {
+                                next[target as usize] += n
+                            }
+                            Edge::Lost => *ends.entry(End::Side(pos)).or_insert(0) += n,
+                            Edge::Clamped => {
+                                next[target.clamp(0, width as isize - 1) as usize] += n
+                            }
+                        }
+                    }
+                }
             }
-        } else {
-            res += dp(diagram, line_index + 1, beam_index, cache);
+        }
+        counts = next;
+    }
+
+    for (col, &n) in counts.iter().enumerate() {
+        if n > 0 {
+            ends.insert(End::Bottom(col), n);
+        }
+    }
+
+    Sweep {
+        splits,
+        ends,
+        through,
+    }
+}
+
+// Timelines sorties par le bas, colonne par colonne
+fn exit_columns(sweep: &Sweep) -> Vec<u128> {
+    let mut columns = vec![0; sweep.through.width()];
+    for (end, &n) in &sweep.ends {
+        if let End::Bottom(col) = *end {
+            columns[col] = n;
         }
     }
+    columns
+}
 
-    cache[line_index][beam_index] = Some(res);
+// Une ligne par splitter (case à plusieurs branches) puis une par colonne de sortie,
+// sur la ligne fictive juste sous la grille
+fn stats_csv(manifold: &Grid<Cell>, sweep: &Sweep) -> String {
+    let mut out = String::from("kind,row,col,hit,timelines\n");
 
-    res
+    for (pos, cell) in manifold.iter() {
+        if matches!(cell, Cell::Branch(offsets) if offsets.len() > 1) {
+            let n = sweep.through[pos];
+            out += &format!("splitter,{},{},{},{}\n", pos.row, pos.col, n > 0, n);
+        }
+    }
+    for (col, n) in exit_columns(sweep).into_iter().enumerate() {
+        out += &format!("exit,{},{},{},{}\n", manifold.height(), col, n > 0, n);
+    }
+
+    out
 }
 
-fn part2(diagram: &[Vec<char>]) -> usize {
-    let width = diagram[0].len();
-    let height = diagram.len();
-    let start_index = diagram[0].iter().position(|&s| s == 'S').unwrap();
+// Nuances du plus faible au plus fort ; '.' : aucun faisceau
+const SHADES: &[u8] = b".:-=+*#%@";
 
-    let mut cache = vec![vec![None; width]; height];
+// Échelle logarithmique : le nombre de timelines double à chaque splitter
+fn shade(n: u128, max: u128) -> char {
+    if n == 0 {
+        return SHADES[0] as char;
+    }
+    let levels = SHADES.len() as u32 - 1;
+    let level = 1 + n.ilog2() * (levels - 1) / max.ilog2().max(1);
+    SHADES[level as usize] as char
+}
+
+// Carte de chaleur des passages, suivie d'une ligne pour les sorties par le bas
+fn heatmap(sweep: &Sweep) -> String {
+    let exits = exit_columns(sweep);
+    let max = sweep
+        .through
+        .iter()
+        .map(|(_, &n)| n)
+        .chain(exits.iter().copied())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for row in sweep.through.rows().chain(std::iter::once(&exits[..])) {
+        out.extend(row.iter().map(|&n| shade(n, max)));
+        out.push('\n');
+    }
+    out
+}
+
+fn part1(sweep: &Sweep) -> usize {
+    sweep.splits
+}
 
-    // 1 timeline de départ en partant de S
-    // La timeline se duplique à chaque splitter '^'
-    1 + dp(diagram, 1, start_index, &mut cache)
+fn part2(sweep: &Sweep) -> u128 {
+    sweep.exits(Semantics::Timelines)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn sweep_str(input: &str) -> Sweep {
+        sweep(&parse(input, &CellTable::default()).unwrap(), Edge::Lost)
+    }
+
     #[test]
     fn test_part1_example() {
         let example_input = include_str!("../example.txt");
-        let diagram = parse(example_input);
-        assert_eq!(part1(&diagram), 21);
+        assert_eq!(part1(&sweep_str(example_input)), 21);
     }
 
     #[test]
     fn test_part2_example() {
         let example_input = include_str!("../example.txt");
-        let diagram = parse(example_input);
-        assert_eq!(part2(&diagram), 40);
+        assert_eq!(part2(&sweep_str(example_input)), 40);
+    }
+
+    #[test]
+    fn test_tall_manifold() {
+        // 10 000 lignes dont 100 de splitters en quinconce : la récursion aurait débordé
+        // la pile, et le nombre de timelines dépasse u64
+        let width = 41;
+        let mut rows = vec![format!("{}S{}", ".".repeat(20), ".".repeat(20))];
+        for i in 0..10_000 {
+            let line: String = (0..width)
+                .map(|col| match i % 200 {
+                    0 if col % 2 == 0 => '^',
+                    100 if col % 2 == 1 => '^',
+                    _ => '.',
+                })
+                .collect();
+            rows.push(line);
+        }
+        let result = sweep_str(&rows.join("\n"));
+        assert!(result.splits > 0);
+        assert!(part2(&result) > u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_cell_kinds() {
+        // Deux sources, un déflecteur vers la droite, un absorbeur et un splitter à trois voies
+        let result = sweep_str(
+            "\
+.S...S.
+.......
+.>...Y.
+.......
+..#.^..",
+        );
+        assert_eq!(result.splits, 2);
+        assert_eq!(
+            result.ends,
+            BTreeMap::from([
+                (End::Bottom(3), 1),
+                (End::Bottom(5), 2),
+                (End::Bottom(6), 1),
+                (End::Absorbed(Pos::new(4, 2)), 1),
+            ])
+        );
+        assert_eq!(result.exits(Semantics::Timelines), 5);
+        assert_eq!(result.exits(Semantics::Beams), 4);
+    }
+
+    #[test]
+    fn test_semantics() {
+        // Les deux branches du premier splitter se rejoignent en colonne 2
+        let result = sweep_str("..S..\n..^..\n.^.^.\n.....");
+        assert_eq!(result.exits(Semantics::Timelines), 4);
+        assert_eq!(result.exits(Semantics::Beams), 3);
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let args: Vec<String> = ["day07", "--cell", "X=-2,2", "--cell", "^=absorb"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let table = CellTable::from_args(&args).unwrap();
+        let manifold = parse("..S..\n..X..\n^....", &table).unwrap();
+        let result = sweep(&manifold, Edge::Lost);
+        assert_eq!(result.splits, 1);
+        assert_eq!(result.ends.get(&End::Absorbed(Pos::new(2, 0))), Some(&1));
+        assert_eq!(result.ends.get(&End::Bottom(4)), Some(&1));
+
+        assert_eq!(
+            parse("S\n?", &CellTable::default())
+                .unwrap_err()
+                .to_string(),
+            "unknown cell '?' at line 2, column 1"
+        );
+        assert_eq!(
+            parse("S..\n..", &CellTable::default()).unwrap_err(),
+            ManifoldError::Ragged(RaggedRow {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            parse("S..\n..", &CellTable::default())
+                .unwrap_err()
+                .to_string(),
+            "line 2 has 2 cells, expected 3"
+        );
+        assert!(CellTable::from_args(&["--cell".to_string(), "X=a".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let manifold = parse("..S..\n..^..\n.....\n.^.^.\n.....", &CellTable::default()).unwrap();
+        let result = sweep(&manifold, Edge::Lost);
+
+        let csv = stats_csv(&manifold, &result);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "kind,row,col,hit,timelines");
+        assert_eq!(lines[1], "splitter,1,2,true,1");
+        assert_eq!(lines[2], "splitter,3,1,true,1");
+        assert_eq!(lines[3], "splitter,3,3,true,1");
+        assert_eq!(exit_columns(&result), vec![1, 0, 2, 0, 1]);
+        assert_eq!(
+            lines[4..].join("|"),
+            "exit,5,0,true,1|exit,5,1,false,0|exit,5,2,true,2|exit,5,3,false,0|exit,5,4,true,1"
+        );
+
+        assert_eq!(
+            heatmap(&result),
+            "..:..\n..:..\n.:.:.\n.:.:.\n:.@.:\n:.@.:\n"
+        );
+    }
+
+    #[test]
+    fn test_stats_example() {
+        let manifold = parse(include_str!("../example.txt"), &CellTable::default()).unwrap();
+        let result = sweep(&manifold, Edge::Lost);
+
+        let splitters: Vec<u128> = manifold
+            .iter()
+            .filter(|(_, cell)| matches!(cell, Cell::Branch(_)))
+            .map(|(pos, _)| result.through[pos])
+            .collect();
+        assert_eq!(splitters.len(), 22);
+        // Un seul splitter n'est jamais atteint (part1 = 21)
+        assert_eq!(splitters.iter().filter(|&&n| n == 0).count(), 1);
+        assert_eq!(exit_columns(&result).iter().sum::<u128>(), 40);
+    }
+
+    #[test]
+    fn test_border_splitters() {
+        // Splitters sur les colonnes 0 et width-1
+        let input = "S..S\n^..^\n....";
+        let manifold = parse(input, &CellTable::default()).unwrap();
+
+        let lost = sweep(&manifold, Edge::Lost);
+        assert_eq!(lost.splits, 2);
+        assert_eq!(lost.ends.get(&End::Side(Pos::new(1, 0))), Some(&1));
+        assert_eq!(lost.ends.get(&End::Side(Pos::new(1, 3))), Some(&1));
+        assert_eq!(exit_columns(&lost), vec![0, 1, 1, 0]);
+        assert_eq!(part2(&lost), 4);
+
+        let clamped = sweep(&manifold, Edge::Clamped);
+        assert_eq!(clamped.splits, 2);
+        assert!(!clamped.ends.keys().any(|end| matches!(end, End::Side(_))));
+        assert_eq!(exit_columns(&clamped), vec![1, 1, 1, 1]);
+        assert_eq!(part2(&clamped), 4);
+        assert_eq!(clamped.exits(Semantics::Beams), 4);
+
+        // Un déflecteur plus large que la grille est ramené au bord
+        let table = CellTable::from_args(&["--cell".to_string(), "X=-5".to_string()]).unwrap();
+        let manifold = parse(".S\n.X\n..", &table).unwrap();
+        assert_eq!(exit_columns(&sweep(&manifold, Edge::Clamped)), vec![1, 0]);
+        assert_eq!(part2(&sweep(&manifold, Edge::Lost)), 1);
+        assert_eq!(exit_columns(&sweep(&manifold, Edge::Lost)), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_splitters_on_last_row() {
+        // Le splitter de la dernière ligne compte et ses branches sortent par le bas
+        let result = sweep_str("..S..\n.....\n..^..");
+        assert_eq!(part1(&result), 1);
+        assert_eq!(exit_columns(&result), vec![0, 1, 0, 1, 0]);
+        assert_eq!(part2(&result), 2);
+
+        // Dernière ligne et bord à la fois
+        let result = sweep_str("S.\n^.");
+        assert_eq!(part1(&result), 1);
+        assert_eq!(exit_columns(&result), vec![0, 1]);
+        assert_eq!(result.ends.get(&End::Side(Pos::new(1, 0))), Some(&1));
+        assert_eq!(part2(&result), 2);
+
+        // Grille d'une seule ligne : la source sort directement par le bas
+        assert_eq!(part2(&sweep_str(".S.")), 1);
     }
 }
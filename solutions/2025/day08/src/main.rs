@@ -84,74 +84,95 @@ fn build_edges(nodes: &[Node]) -> Vec<Edge> {
     edges
 }
 
-fn get_components(n: usize, adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
-    let mut visited = vec![false; n];
-    let mut components = Vec::new();
+// Union-find : compression de chemin et union par taille
+struct Dsu {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
 
-    for start in 0..n {
-        if visited[start] {
-            continue;
+impl Dsu {
+    fn new(n: usize) -> Dsu {
+        Dsu {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
         }
+    }
 
-        let mut comp = Vec::new();
-        let mut stack = vec![start];
-        visited[start] = true;
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
 
-        while let Some(u) = stack.pop() {
-            comp.push(u);
+        // Compression : chaque noeud du chemin pointe directement sur la racine
+        let mut node = x;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
 
-            for &v in &adj[u] {
-                if !visited[v] {
-                    visited[v] = true;
-                    stack.push(v);
-                }
-            }
+        root
+    }
+
+    // Renvoie false si a et b étaient déjà dans la même composante
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
         }
-        components.push(comp);
+
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    // Taille de chaque composante
+    fn component_sizes(&self) -> Vec<usize> {
+        (0..self.parent.len())
+            .filter(|&x| self.parent[x] == x)
+            .map(|root| self.size[root])
+            .collect()
     }
-    components
 }
 
-fn part1(nodes: &[Node], k: usize) -> usize {
+fn sorted_edges(nodes: &[Node]) -> Vec<Edge> {
     let mut edges = build_edges(nodes);
     edges.sort_unstable_by_key(|e| e.distance);
+    edges
+}
 
-    let top_edges = &edges[..k];
-
-    let mut adj_list = vec![Vec::new(); nodes.len()];
+fn part1(nodes: &[Node], k: usize) -> usize {
+    let mut dsu = Dsu::new(nodes.len());
 
-    for e in top_edges {
-        adj_list[e.node1].push(e.node2);
-        adj_list[e.node2].push(e.node1);
+    // Les k paires les plus proches, même celles déjà reliées
+    for e in sorted_edges(nodes).iter().take(k) {
+        dsu.union(e.node1, e.node2);
     }
 
-    let components = get_components(nodes.len(), &adj_list);
-    let mut comp_sizes: Vec<usize> = components.iter().map(|c| c.len()).collect();
-    comp_sizes.sort_unstable();
-    let n = comp_sizes.len() - 3;
+    let mut comp_sizes = dsu.component_sizes();
+    comp_sizes.sort_unstable_by(|a, b| b.cmp(a));
 
-    comp_sizes[n..].iter().fold(1, |acc, i| acc * i)
+    comp_sizes.iter().take(3).product()
 }
 
 fn part2(nodes: &[Node]) -> usize {
-    let mut res = 0;
-    let mut edges = build_edges(nodes);
+    let mut dsu = Dsu::new(nodes.len());
 
-    edges.sort_unstable_by_key(|e| e.distance);
-
-    let mut adj_list = vec![Vec::new(); nodes.len()];
-
-    for e in edges {
-        adj_list[e.node1].push(e.node2);
-        adj_list[e.node2].push(e.node1);
-        let components = get_components(nodes.len(), &adj_list);
-        if components.len() == 1 {
-            res = nodes[e.node1].x * nodes[e.node2].x;
-            break;
+    // L'arête qui fusionne les deux dernières composantes (Kruskal)
+    for e in sorted_edges(nodes) {
+        if dsu.union(e.node1, e.node2) && dsu.components == 1 {
+            return nodes[e.node1].x * nodes[e.node2].x;
         }
     }
 
-    res
+    0
 }
 
 #[cfg(test)]
@@ -171,4 +192,20 @@ mod tests {
         let boxes = parse(example_input);
         assert_eq!(part2(&boxes), 25272);
     }
+
+    #[test]
+    fn test_dsu() {
+        let mut dsu = Dsu::new(6);
+        assert!(dsu.union(0, 1));
+        assert!(dsu.union(2, 3));
+        assert!(dsu.union(1, 3));
+        assert!(!dsu.union(0, 2));
+        assert_eq!(dsu.find(3), dsu.find(0));
+        assert_ne!(dsu.find(4), dsu.find(0));
+        assert_eq!(dsu.components, 3);
+
+        let mut sizes = dsu.component_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 4]);
+    }
 }